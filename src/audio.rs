@@ -3,13 +3,19 @@
 //! [General bus](General)
 //! [Music sampler pool](Music)
 //! [Sfx sampler pool](Sfx)
+//! [Spatial sfx sampler pool](SpatialSfx)
 //!
 use bevy::prelude::*;
 use bevy_seedling::{pool::SamplerPool, prelude::*, sample::Sample};
 use serde::{Deserialize, Serialize};
 
+/// World units per unit of distance the spatial node attenuates by. Depth is ignored, the
+/// camera sits far in front of everything it hears.
+const SPATIAL_SCALE: Vec3 = Vec3::new(0.01, 0.01, 0.0);
+
 pub fn plugin(app: &mut App) {
     app.add_plugins(SeedlingPlugin::default())
+        .insert_resource(DefaultSpatialScale(SPATIAL_SCALE))
         .add_systems(Startup, spawn_pools);
 }
 
@@ -69,8 +75,15 @@ impl Default for Sound {
 
 fn spawn_pools(mut cmds: Commands) {
     cmds.spawn((General, VolumeNode::default()));
-    cmds.spawn(SamplerPool(Music)).connect(General);
+    // each music sample gets a volume of its own, so soundtrack layers can fade separately
+    cmds.spawn((SamplerPool(Music), sample_effects![VolumeNode::default()]))
+        .connect(General);
     cmds.spawn(SamplerPool(Sfx)).connect(General);
+    cmds.spawn((
+        SamplerPool(SpatialSfx),
+        sample_effects![SpatialBasicNode::default()],
+    ))
+    .connect(General);
 }
 
 /// An organizational marker component that should be added to a spawned [`SamplePlayer`] if it's in the
@@ -124,3 +137,29 @@ pub fn sfx_looping(handle: Handle<Sample>, vol: f32) -> impl Bundle {
             .looping(),
     )
 }
+
+/// Sound effects that happen somewhere in the world. They pan and fade by their position
+/// relative to the [`SpatialListener2D`] on the main camera.
+#[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash, Default, Reflect)]
+#[reflect(Component)]
+pub struct SpatialSfx;
+
+/// An sfx audio instance played once at `translation` in the world
+pub fn spatial_sfx(handle: Handle<Sample>, vol: f32, translation: Vec3) -> impl Bundle {
+    (
+        SpatialSfx,
+        SamplePlayer::new(handle).with_volume(Volume::Linear(vol)),
+        Transform::from_translation(translation),
+    )
+}
+
+/// A looping sfx audio instance, spawn it as a child of whatever makes the sound
+pub fn spatial_sfx_looping(handle: Handle<Sample>, vol: f32) -> impl Bundle {
+    (
+        SpatialSfx,
+        SamplePlayer::new(handle)
+            .with_volume(Volume::Linear(vol))
+            .looping(),
+        Transform::default(),
+    )
+}
@@ -0,0 +1,310 @@
+//! Command line arguments, plus config overrides from the environment.
+//!
+//! Everything a tester needs to get back to a specific situation: start in a level, change
+//! a value from `config.ron`, fix the particle seed or open a level in the editor.
+
+use crate::{
+    game::{levels::EditorMode, replay::LATEST_REPLAY_PATH},
+    prelude::*,
+};
+use bevy::{
+    prelude::*,
+    reflect::GetPath,
+    window::{MonitorSelection, WindowMode},
+};
+use std::str::FromStr;
+
+/// Whitespace separated `key=value` config overrides, applied before the `--config` ones.
+pub const CONFIG_ENV: &str = "CHAI_REACTION_CONFIG";
+
+pub const USAGE: &str = "\
+usage: chai-reaction [options]
+       chai-reaction replay [file]
+       chai-reaction bake-colliders [assets dir]
+       chai-reaction check-content [assets dir]
+
+options:
+  --level <name>        start in a level, by its name or file name like `first`
+  --config <key=value>  override a value from config.ron, like `physics.gravity=50`
+  --seed <number>       seed every level's particles with the same number
+  --windowed            start in a window
+  --fullscreen          start in borderless fullscreen
+  --editor <path>       edit a level file, a bare name like `first` is looked up as
+                        assets/levels/first.ron, needs the `dev` feature
+  -h, --help            show this message
+
+config overrides are also read from the CHAI_REACTION_CONFIG environment variable.";
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        PreUpdate,
+        apply_config_overrides.run_if(resource_exists_and_changed::<Config>),
+    )
+    .add_systems(OnEnter(Screen::Title), start_requested_level);
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct Args {
+    pub command: Option<Command>,
+    /// Level to start in, by [`LevelConfig::name`] or the file name in `levels.ron`.
+    pub level: Option<String>,
+    pub config: Vec<ConfigOverride>,
+    pub seed: Option<u64>,
+    pub window_mode: Option<WindowMode>,
+    /// Level file to open in the editor.
+    pub editor: Option<String>,
+}
+
+/// Commands that do something other than starting the game normally.
+#[derive(Debug, Clone)]
+pub enum Command {
+    Help,
+    Replay(String),
+    BakeColliders(String),
+    CheckContent(String),
+}
+
+/// A `key=value` pair, the key being a path into [`Config`] like `physics.gravity`.
+#[derive(Debug, Clone)]
+pub struct ConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+impl Args {
+    /// The arguments the game was started with, after the overrides from [`CONFIG_ENV`].
+    pub fn from_env() -> Result<Self, String> {
+        let mut args = Self::parse(std::env::args().skip(1))?;
+        if let Ok(overrides) = std::env::var(CONFIG_ENV) {
+            let overrides = overrides
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("{CONFIG_ENV}: {e}"))?;
+            args.config.splice(0..0, overrides);
+        }
+        Ok(args)
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--level" => parsed.level = Some(value()?),
+                "--config" => parsed.config.push(value()?.parse()?),
+                "--seed" => {
+                    let seed = value()?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| format!("--seed: {seed} isn't a number"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--windowed" => parsed.window_mode = Some(WindowMode::Windowed),
+                "--fullscreen" => {
+                    let fullscreen = WindowMode::BorderlessFullscreen(MonitorSelection::Current);
+                    parsed.window_mode = Some(fullscreen);
+                }
+                "--editor" if cfg!(feature = "dev") => {
+                    parsed.editor = Some(level_file(&value()?));
+                }
+                "--editor" => return Err("--editor needs a build with the dev feature".into()),
+                "-h" | "--help" => parsed.command = Some(Command::Help),
+                "replay" | "bake-colliders" | "check-content" if parsed.command.is_none() => {
+                    let path = args.next_if(|arg| !arg.starts_with('-'));
+                    let path = |default: &str| path.unwrap_or_else(|| default.to_string());
+                    parsed.command = Some(match arg.as_str() {
+                        "replay" => Command::Replay(path(LATEST_REPLAY_PATH)),
+                        "bake-colliders" => Command::BakeColliders(path("assets")),
+                        _ => Command::CheckContent(path("assets")),
+                    });
+                }
+                _ => return Err(format!("unknown argument {arg}")),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// A level file as given, or `assets/levels/<name>.ron` for a bare level name.
+fn level_file(level: &str) -> String {
+    if level.ends_with(".ron") || level.contains(['/', '\\']) {
+        level.to_string()
+    } else {
+        format!("assets/levels/{level}.ron")
+    }
+}
+
+impl FromStr for ConfigOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("config override {s} isn't key=value"))?;
+        Ok(Self {
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+impl ConfigOverride {
+    pub fn apply(&self, config: &mut Config) -> Result<(), String> {
+        let key = self.key.as_str();
+        let field = config
+            .reflect_path_mut(key)
+            .map_err(|e| format!("{key}: {e}"))?;
+        let invalid = || format!("{key}: {} isn't a valid value", self.value);
+        if let Some(field) = field.try_downcast_mut::<f32>() {
+            *field = self.value.parse().map_err(|_| invalid())?;
+        } else if let Some(field) = field.try_downcast_mut::<u32>() {
+            *field = self.value.parse().map_err(|_| invalid())?;
+        } else if let Some(field) = field.try_downcast_mut::<bool>() {
+            *field = self.value.parse().map_err(|_| invalid())?;
+        } else if let Some(field) = field.try_downcast_mut::<String>() {
+            *field = self.value.clone();
+        } else {
+            return Err(format!(
+                "{key}: only numbers, booleans and text can be overridden"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Applies the overrides whenever the config is loaded, including hot reloads.
+fn apply_config_overrides(
+    args: Option<Res<Args>>,
+    mut config: ResMut<Config>,
+    mut reported: Local<bool>,
+) {
+    let Some(args) = args.filter(|args| !args.config.is_empty()) else {
+        return;
+    };
+    let mut patched = config.clone();
+    for config_override in &args.config {
+        if let Err(e) = config_override.apply(&mut patched) {
+            if !*reported {
+                error!("--config {e}");
+            }
+        }
+    }
+    *reported = true;
+    // replacing the config changes it again, the second pass finds nothing left to do
+    if config.reflect_partial_eq(&patched) != Some(true) {
+        *config = patched;
+    }
+}
+
+/// Goes straight to the level from `--level` the first time the title screen is reached.
+fn start_requested_level(
+    mut commands: Commands,
+    args: Option<ResMut<Args>>,
+    level_list: Res<LevelList>,
+    level_configs: Res<Assets<LevelConfig>>,
+    editor_mode: Res<EditorMode>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(name) = args.and_then(|mut args| args.level.take()) else {
+        return;
+    };
+    if editor_mode.0 {
+        return;
+    }
+    let level = level_list.0.iter().find(|handle| {
+        let file = handle.path().and_then(|path| path.path().file_stem());
+        file.is_some_and(|file| file.eq_ignore_ascii_case(&name))
+            || level_configs
+                .get(*handle)
+                .is_some_and(|level| level.name.eq_ignore_ascii_case(&name))
+    });
+    let Some(level) = level else {
+        error!("--level: no level called \"{name}\"");
+        return;
+    };
+    commands.insert_resource(LoadedLevel(level.clone()));
+    next_screen.set(Screen::Gameplay);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn config() -> Config {
+        let source = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/config.ron"));
+        ron::de::from_str(source).expect("config.ron")
+    }
+
+    #[test]
+    fn options_need_a_value() {
+        assert_eq!(parse(&["--seed"]).unwrap_err(), "--seed needs a value");
+        let error = parse(&["--level", "first", "--config"]).unwrap_err();
+        assert_eq!(error, "--config needs a value");
+    }
+
+    #[test]
+    fn seeds_are_numbers() {
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        let error = parse(&["--seed", "forty-two"]).unwrap_err();
+        assert_eq!(error, "--seed: forty-two isn't a number");
+    }
+
+    #[test]
+    fn unknown_arguments_are_an_error() {
+        assert_eq!(
+            parse(&["--gravity"]).unwrap_err(),
+            "unknown argument --gravity"
+        );
+    }
+
+    #[test]
+    fn overrides_are_key_value_pairs() {
+        let parsed: ConfigOverride = " physics.gravity = 50 ".parse().unwrap();
+        assert_eq!(
+            (parsed.key.as_str(), parsed.value.as_str()),
+            ("physics.gravity", "50")
+        );
+        let parsed: ConfigOverride = "key=a=b".parse().unwrap();
+        assert_eq!((parsed.key.as_str(), parsed.value.as_str()), ("key", "a=b"));
+
+        let error = "physics.gravity".parse::<ConfigOverride>().unwrap_err();
+        assert_eq!(error, "config override physics.gravity isn't key=value");
+        let error = parse(&["--config", "physics.gravity"]).unwrap_err();
+        assert_eq!(error, "config override physics.gravity isn't key=value");
+    }
+
+    #[test]
+    fn overrides_set_numbers_in_the_config() {
+        let mut config = config();
+        let gravity: ConfigOverride = "physics.gravity=50".parse().unwrap();
+        gravity.apply(&mut config).unwrap();
+        assert_eq!(config.physics.gravity, 50.0);
+
+        let invalid: ConfigOverride = "physics.gravity=down".parse().unwrap();
+        assert!(invalid.apply(&mut config).is_err());
+        assert_eq!(config.physics.gravity, 50.0);
+    }
+
+    #[test]
+    fn only_scalars_can_be_overridden() {
+        let mut config = config();
+        let physics: ConfigOverride = "physics=50".parse().unwrap();
+        assert_eq!(
+            physics.apply(&mut config).unwrap_err(),
+            "physics: only numbers, booleans and text can be overridden"
+        );
+    }
+
+    #[test]
+    fn bare_level_names_are_looked_up_in_the_levels_folder() {
+        assert_eq!(level_file("first"), "assets/levels/first.ron");
+        assert_eq!(level_file("drafts/new.ron"), "drafts/new.ron");
+        assert_eq!(level_file("new.ron"), "new.ron");
+    }
+}
@@ -17,6 +17,57 @@ pub(super) fn plugin(app: &mut App) {
         app.insert_gizmo_config(PhysicsGizmos::none(), GizmoConfig::default());
         app.add_systems(Update, log_transitions::<Screen>);
         app.add_observer(toggle_debug_ui);
+
+        app.init_resource::<ColliderOverlay>()
+            .add_observer(toggle_collider_overlay)
+            .add_systems(
+                Update,
+                draw_collider_overlay.run_if(|overlay: Res<ColliderOverlay>| overlay.0),
+            );
+    }
+}
+
+/// Outlines the convex pieces of every collider generated from a mesh image.
+#[cfg(feature = "dev_native")]
+#[derive(Resource, Default)]
+struct ColliderOverlay(bool);
+
+#[cfg(feature = "dev_native")]
+fn toggle_collider_overlay(
+    _: Trigger<OnColliderOverlayToggle>,
+    mut overlay: ResMut<ColliderOverlay>,
+) {
+    overlay.0 = !overlay.0;
+}
+
+#[cfg(feature = "dev_native")]
+fn draw_collider_overlay(
+    mut gizmos: Gizmos,
+    colliders: Query<(&Collider, &GlobalTransform), With<GeneratedCollider>>,
+) {
+    for (collider, transform) in &colliders {
+        let Some(compound) = collider.shape().as_compound() else {
+            continue;
+        };
+        for (index, (isometry, shape)) in compound.shapes().iter().enumerate() {
+            let Some(polygon) = shape.as_convex_polygon() else {
+                continue;
+            };
+            let mut points: Vec<Vec2> = polygon
+                .points()
+                .iter()
+                .map(|p| {
+                    let p = isometry * p;
+                    transform
+                        .transform_point(Vec3::new(p.x, p.y, 0.0))
+                        .truncate()
+                })
+                .collect();
+            points.extend(points.first().copied());
+            // alternate colors so neighbouring pieces are easy to tell apart
+            let color = Color::hsl(index as f32 * 47.0 % 360.0, 0.9, 0.6);
+            gizmos.linestrip_2d(points, color);
+        }
     }
 }
 
@@ -1,54 +1,233 @@
-use crate::prelude::*;
-use bevy::prelude::*;
+use crate::{game::settings::Rebinding, prelude::*};
+use bevy::{
+    input::{gestures::PinchGesture, mouse::AccumulatedMouseMotion},
+    prelude::*,
+    render::camera::ScalingMode,
+    window::PrimaryWindow,
+};
+use bevy_seedling::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+/// World units per second the camera pans at, before zoom.
+const PAN_SPEED: f32 = 400.0;
+/// Distance from the window border, in logical pixels, that scrolls the camera.
+const EDGE_SCROLL_MARGIN: f32 = 8.0;
+/// How much one wheel notch, or one frame of scrolling, changes the zoom.
+const ZOOM_STEP: f32 = 0.1;
+/// Closest the camera can zoom in, as an orthographic scale.
+const MIN_ZOOM: f32 = 0.5;
+/// Side of the black quads that hide everything outside the level bounds.
+const MASK_SIZE: f32 = 100_000.0;
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, spawn_camera.run_if(resource_added::<Config>));
+    app.add_systems(Update, spawn_camera.run_if(resource_added::<Config>))
+        .add_systems(
+            Update,
+            (
+                update_bounds.run_if(resource_exists_and_changed::<LoadedLevel>),
+                (reset_camera, fit_masks).run_if(resource_exists_and_changed::<CameraBounds>),
+                (pan_camera, zoom_camera)
+                    .run_if(in_state(Screen::Gameplay))
+                    .run_if(|settings: Res<Settings>| settings.modals.is_empty())
+                    .run_if(not(resource_exists::<Rebinding>)),
+                clamp_camera.run_if(resource_exists::<CameraBounds>),
+            )
+                .chain(),
+        );
 }
 
 #[derive(Component)]
 pub struct MainCamera;
 
+/// World-space area of the loaded level. The camera never shows anything past it.
+#[derive(Resource, Deref, Clone, Copy, Debug)]
+pub struct CameraBounds(pub Rect);
+
+/// One of the black quads around [`CameraBounds`], on the side it points to.
+#[derive(Component)]
+struct BoundsMask(Vec2);
+
 pub fn spawn_camera(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     config: Res<Config>,
-    mut ui_scale: ResMut<UiScale>,
 ) {
-    let screen_width = 16.0 * config.screen_size_in_tiles.x as f32 + 16.;
-    let screen_height = 16.0 * config.screen_size_in_tiles.y as f32 + 48. ;
+    let view = config.view_size();
     commands.spawn((
         Camera2d,
         Projection::Orthographic(OrthographicProjection {
-            scaling_mode: bevy::render::camera::ScalingMode::AutoMin {
-                min_width: screen_width,
-                min_height: screen_height,
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: view.x,
+                min_height: view.y,
             },
             ..OrthographicProjection::default_2d()
         }),
         MainCamera,
         IsDefaultUiCamera,
+        SpatialListener2D,
     ));
+    commands.insert_resource(CameraBounds(config.screen_bounds()));
 
     // Mask overlay
-    commands.spawn((
-        Mesh2d(meshes.add(Rectangle::new(10000.0, 10000.0))),
-        MeshMaterial2d(materials.add(Color::BLACK)),
-        Transform::from_xyz(-5000.0 - screen_width * 0.5, 0.0, 100.0),
-    ));
-    commands.spawn((
-        Mesh2d(meshes.add(Rectangle::new(10000.0, 10000.0))),
-        MeshMaterial2d(materials.add(Color::BLACK)),
-        Transform::from_xyz(5000.0 + screen_width * 0.5, 0.0, 100.0),
-    ));
-    commands.spawn((
-        Mesh2d(meshes.add(Rectangle::new(10000.0, 10000.0))),
-        MeshMaterial2d(materials.add(Color::BLACK)),
-        Transform::from_xyz(0.0, 5000.0 + (screen_height - 64.) * 0.5, 100.0),
-    ));
-    commands.spawn((
-        Mesh2d(meshes.add(Rectangle::new(10000.0, 10000.0))),
-        MeshMaterial2d(materials.add(Color::BLACK)),
-        Transform::from_xyz(0.0, -5000.0 - (screen_height - 64.)* 0.5, 100.0),
-    ));
+    let mask = meshes.add(Rectangle::new(MASK_SIZE, MASK_SIZE));
+    let black = materials.add(Color::BLACK);
+    for side in [Vec2::NEG_X, Vec2::X, Vec2::Y, Vec2::NEG_Y] {
+        commands.spawn((
+            BoundsMask(side),
+            Mesh2d(mask.clone()),
+            MeshMaterial2d(black.clone()),
+            Transform::from_xyz(0.0, 0.0, 100.0),
+        ));
+    }
+}
+
+fn update_bounds(
+    mut commands: Commands,
+    config: Res<Config>,
+    loaded_level: Res<LoadedLevel>,
+    level_configs: Res<Assets<LevelConfig>>,
+) {
+    let bounds = level_configs
+        .get(&loaded_level.0)
+        .and_then(|level| level.bounds)
+        .unwrap_or_else(|| config.screen_bounds());
+    commands.insert_resource(CameraBounds(bounds));
+}
+
+/// Centers the camera on a freshly loaded level.
+fn reset_camera(
+    bounds: Res<CameraBounds>,
+    camera: Single<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let (mut transform, mut projection) = camera.into_inner();
+    transform.translation = bounds.center().extend(transform.translation.z);
+    if let Projection::Orthographic(ortho) = &mut *projection {
+        ortho.scale = 1.0;
+    }
+}
+
+fn fit_masks(bounds: Res<CameraBounds>, mut masks: Query<(&BoundsMask, &mut Transform)>) {
+    for (BoundsMask(side), mut transform) in &mut masks {
+        let offset = *side * (bounds.half_size() + MASK_SIZE * 0.5);
+        transform.translation = (bounds.center() + offset).extend(transform.translation.z);
+    }
+}
+
+fn pan_camera(
+    time: Res<Time>,
+    config: Res<Config>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    action: Query<&ActionState<Action>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&mut Transform, &Projection), With<MainCamera>>,
+) -> Result {
+    let (mut transform, projection) = camera.into_inner();
+    let Projection::Orthographic(ortho) = projection else {
+        return Ok(());
+    };
+    let view = view_size(config.view_size(), &window, ortho.scale);
+    let units_per_pixel = view.x / window.width();
+
+    let mut direction = action.single()?.axis_pair(&Action::Pan);
+    if window.focused {
+        if let Some(cursor) = window.cursor_position() {
+            let size = window.size();
+            if cursor.x < EDGE_SCROLL_MARGIN {
+                direction.x -= 1.0;
+            } else if cursor.x > size.x - EDGE_SCROLL_MARGIN {
+                direction.x += 1.0;
+            }
+            // window coordinates grow downwards
+            if cursor.y < EDGE_SCROLL_MARGIN {
+                direction.y += 1.0;
+            } else if cursor.y > size.y - EDGE_SCROLL_MARGIN {
+                direction.y -= 1.0;
+            }
+        }
+    }
+    let mut delta = direction.clamp_length_max(1.0) * PAN_SPEED * ortho.scale * time.delta_secs();
+
+    if mouse.pressed(MouseButton::Middle) {
+        delta += Vec2::new(-motion.delta.x, motion.delta.y) * units_per_pixel;
+    }
+
+    transform.translation += delta.extend(0.0);
+    Ok(())
+}
+
+fn zoom_camera(
+    config: Res<Config>,
+    bounds: Res<CameraBounds>,
+    mut pinches: EventReader<PinchGesture>,
+    action: Query<&ActionState<Action>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut projection: Single<&mut Projection, With<MainCamera>>,
+) -> Result {
+    let Projection::Orthographic(ortho) = &mut **projection else {
+        return Ok(());
+    };
+    // lines and pixels come through the same axis, so only the direction is used: a wheel
+    // notch or a frame of trackpad scrolling is one step either way
+    let scroll = action.single()?.value(&Action::Zoom);
+    let mut zoom = if scroll == 0.0 {
+        0.0
+    } else {
+        scroll.signum() * ZOOM_STEP
+    };
+    // a positive pinch spreads the fingers, which zooms in like scrolling up
+    zoom += pinches.read().map(|pinch| pinch.0).sum::<f32>();
+    if zoom == 0.0 {
+        return Ok(());
+    }
+
+    // zooming out stops once the whole level is in view
+    let base = view_size(config.view_size(), &window, 1.0);
+    let max_zoom = (bounds.size() / base).max_element().max(1.0);
+    ortho.scale = (ortho.scale * (1.0 - zoom)).clamp(MIN_ZOOM, max_zoom);
+    Ok(())
+}
+
+/// Keeps the view inside [`CameraBounds`], centering it on axes where the level is smaller
+/// than the view.
+fn clamp_camera(
+    config: Res<Config>,
+    bounds: Res<CameraBounds>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&mut Transform, &Projection), With<MainCamera>>,
+) {
+    let (mut transform, projection) = camera.into_inner();
+    let Projection::Orthographic(ortho) = projection else {
+        return;
+    };
+    let half_view = view_size(config.view_size(), &window, ortho.scale) * 0.5;
+    let min = bounds.min + half_view;
+    let max = bounds.max - half_view;
+
+    let position = transform.translation.truncate();
+    let mut clamped = bounds.center();
+    if min.x < max.x {
+        clamped.x = position.x.clamp(min.x, max.x);
+    }
+    if min.y < max.y {
+        clamped.y = position.y.clamp(min.y, max.y);
+    }
+    if clamped != position {
+        transform.translation = clamped.extend(transform.translation.z);
+    }
+}
+
+/// World-space size of the view, matching [`ScalingMode::AutoMin`] for this window.
+///
+/// Computed here rather than read from the projection area, which only catches up with a
+/// new scale after the camera systems in `PostUpdate`.
+fn view_size(min: Vec2, window: &Window, scale: f32) -> Vec2 {
+    let aspect = window.width() / window.height().max(1.0);
+    let size = if aspect > min.x / min.y {
+        Vec2::new(min.y * aspect, min.y)
+    } else {
+        Vec2::new(min.x, min.x / aspect)
+    };
+    size * scale
 }
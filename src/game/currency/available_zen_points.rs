@@ -33,6 +33,10 @@ fn set_on_level_start(
 }
 
 impl AvailableZenPoints {
+    pub fn amount(&self) -> u32 {
+        self.0
+    }
+
     pub fn buy_if_affordable(&mut self, cost: u32) -> ActionPerformed {
         let affordable = self.0 >= cost;
         if affordable {
@@ -0,0 +1,136 @@
+//! Physics settings that levels can override: gravity, wind, ambient temperature, particle
+//! budget and brewing temperature.
+
+use crate::prelude::*;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How fast, in heat per second, particles drift towards the ambient temperature.
+const AMBIENT_HEAT_RATE: f32 = 0.5;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnExit(Screen::Gameplay), reset_environment)
+        .add_systems(
+            Update,
+            (
+                reset_environment.run_if(resource_added::<Config>),
+                apply_level_environment.run_if(resource_exists_and_changed::<LoadedLevel>),
+                apply_gravity.run_if(resource_exists_and_changed::<Environment>),
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            (apply_wind, apply_ambient_temperature)
+                .before(PhysicsSet::StepSimulation)
+                .run_if(in_state(Screen::Gameplay))
+                .run_if(resource_exists::<Environment>),
+        );
+}
+
+/// Per-level overrides of the physics settings in [`Config`]. Unset fields keep the
+/// global value.
+#[derive(Default, Reflect, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentOverrides {
+    /// Gravity vector in the units of `physics.gravity`, e.g. `(0.0, -50.0)` for half
+    /// gravity or `(100.0, 0.0)` to fall sideways.
+    pub gravity: Option<Vec2>,
+    /// Acceleration of every particle, in world units per second squared.
+    pub wind: Option<Vec2>,
+    /// Heat particles drift towards when nothing heats them. Without it they keep
+    /// their heat.
+    pub ambient_temperature: Option<f32>,
+    pub max_particles: Option<u32>,
+    pub brewing_temperature: Option<f32>,
+}
+
+impl EnvironmentOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The environment of the current level, [`Config`] with the level's overrides applied.
+#[derive(Resource, Debug, Clone)]
+pub struct Environment {
+    pub gravity: Vec2,
+    pub wind: Vec2,
+    pub ambient_temperature: Option<f32>,
+    pub max_particles: usize,
+    pub brewing_temperature: f32,
+}
+
+impl Environment {
+    pub fn new(config: &Config, overrides: &EnvironmentOverrides) -> Self {
+        let gravity = overrides
+            .gravity
+            .unwrap_or(Vec2::NEG_Y * config.physics.gravity);
+        Self {
+            gravity: gravity * 9.81,
+            wind: overrides.wind.unwrap_or_default(),
+            ambient_temperature: overrides.ambient_temperature,
+            max_particles: overrides
+                .max_particles
+                .map_or(config.physics.water.max_particles as usize, |max| {
+                    max as usize
+                }),
+            brewing_temperature: overrides
+                .brewing_temperature
+                .unwrap_or(config.physics.brewing_temperature),
+        }
+    }
+}
+
+fn reset_environment(mut commands: Commands, config: Res<Config>) {
+    commands.insert_resource(Environment::new(&config, &default()));
+}
+
+fn apply_level_environment(
+    mut commands: Commands,
+    config: Res<Config>,
+    loaded_level: Res<LoadedLevel>,
+    level_configs: Res<Assets<LevelConfig>>,
+) {
+    let overrides = level_configs
+        .get(&loaded_level.0)
+        .map(|level| level.environment.clone())
+        .unwrap_or_default();
+    commands.insert_resource(Environment::new(&config, &overrides));
+}
+
+fn apply_gravity(mut commands: Commands, environment: Res<Environment>) {
+    commands.insert_resource(Gravity(environment.gravity));
+}
+
+fn apply_wind(
+    time: Res<Time<Physics>>,
+    environment: Res<Environment>,
+    mut particles: Query<&mut LinearVelocity, With<Particle>>,
+) {
+    if environment.wind == Vec2::ZERO {
+        return;
+    }
+    let delta = environment.wind * time.delta_secs();
+    for mut velocity in &mut particles {
+        velocity.0 += delta;
+    }
+}
+
+fn apply_ambient_temperature(
+    time: Res<Time<Physics>>,
+    environment: Res<Environment>,
+    mut particles: Query<&mut Particle>,
+) {
+    let Some(ambient) = environment.ambient_temperature else {
+        return;
+    };
+    let step = AMBIENT_HEAT_RATE * time.delta_secs();
+    for mut particle in &mut particles {
+        let heat = particle.contents.heat;
+        if heat != ambient {
+            particle.contents.heat = heat + (ambient - heat).clamp(-step, step);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! A cursor moved with the left stick, so the game can be played without a mouse.
+//!
+//! It is a picking pointer of its own: parts are placed and erased with the same `Pointer`
+//! events the mouse sends, and UI buttons get their [`Interaction`] from it as they would
+//! from the mouse. While a part is being placed, the cursor snaps to the part grid.
+
+use crate::{
+    game::{camera::MainCamera, physics::PhysicsState, settings::Rebinding},
+    prelude::*,
+    ui::in_game_ui::machine_part_to_spawn_buttons::{MachinePartButton, MachinePartButtonNode},
+};
+use bevy::{
+    asset::uuid::Uuid,
+    input::mouse::AccumulatedMouseMotion,
+    picking::pointer::{Location, PointerAction, PointerButton, PointerId, PointerInput},
+    render::camera::RenderTarget,
+    ui::Val::*,
+    window::{PrimaryWindow, WindowRef},
+};
+use leafwing_input_manager::prelude::*;
+
+/// Logical pixels per second with the stick fully tilted.
+const CURSOR_SPEED: f32 = 600.0;
+const CURSOR_SIZE: f32 = 16.0;
+const CURSOR_POINTER: PointerId = PointerId::Custom(Uuid::from_u128(0x6368_6169_6375_7273_6f72));
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<GamepadCursor>()
+        .add_systems(Startup, spawn_cursor)
+        .add_systems(
+            Update,
+            (
+                (move_cursor, send_pointer_input).run_if(not(resource_exists::<Rebinding>)),
+                draw_cursor,
+            )
+                .chain()
+                .run_if(resource_exists::<Config>),
+        )
+        .add_observer(cycle_parts)
+        .add_observer(press_button)
+        .add_observer(release_button)
+        .add_observer(hover_button)
+        .add_observer(leave_button);
+}
+
+#[derive(Resource, Default)]
+struct GamepadCursor {
+    /// In window coordinates, before snapping.
+    position: Vec2,
+    /// Where the pointer was last sent, `None` while the mouse is in use.
+    shown_at: Option<Vec2>,
+}
+
+#[derive(Component)]
+struct CursorNode;
+
+fn spawn_cursor(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Gamepad Cursor"),
+        CursorNode,
+        CURSOR_POINTER,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Px(CURSOR_SIZE),
+            height: Px(CURSOR_SIZE),
+            border: UiRect::all(Px(2.0)),
+            ..default()
+        },
+        BorderColor(WHITEISH),
+        BorderRadius::MAX,
+        GlobalZIndex(i32::MAX),
+        Pickable::IGNORE,
+        Visibility::Hidden,
+    ));
+}
+
+fn move_cursor(
+    time: Res<Time>,
+    mouse: Res<AccumulatedMouseMotion>,
+    action: Query<&ActionState<Action>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut cursor: ResMut<GamepadCursor>,
+) -> Result {
+    let state = action.single()?;
+    if mouse.delta != Vec2::ZERO {
+        cursor.shown_at = None;
+        return Ok(());
+    }
+
+    let stick = state.axis_pair(&Action::Cursor);
+    let wakes = stick != Vec2::ZERO || state.just_pressed(&Action::Place);
+    if cursor.shown_at.is_none() && wakes {
+        // start from the mouse, so switching between them doesn't jump
+        cursor.position = window.cursor_position().unwrap_or(window.size() / 2.0);
+    }
+    // window coordinates grow downwards
+    cursor.position += Vec2::new(stick.x, -stick.y) * CURSOR_SPEED * time.delta_secs();
+    cursor.position = cursor.position.clamp(Vec2::ZERO, window.size());
+    if wakes && cursor.shown_at.is_none() {
+        cursor.shown_at = Some(cursor.position);
+    }
+    Ok(())
+}
+
+/// Moves the pointer and presses its button, snapping it to the grid while placing a part.
+fn send_pointer_input(
+    screen: Res<State<Screen>>,
+    picking_state: Res<PickingState>,
+    action: Query<&ActionState<Action>>,
+    window: Single<Entity, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut cursor: ResMut<GamepadCursor>,
+    mut pointer_input: EventWriter<PointerInput>,
+) -> Result {
+    let state = action.single()?;
+    let Some(shown_at) = cursor.shown_at else {
+        return Ok(());
+    };
+    let Some(target) = RenderTarget::Window(WindowRef::Primary).normalize(Some(*window)) else {
+        return Ok(());
+    };
+
+    let mut position = cursor.position;
+    if *screen.get() == Screen::Gameplay && matches!(*picking_state, PickingState::Placing(_)) {
+        let (camera, camera_transform) = *camera;
+        if let Ok(world) = camera.viewport_to_world_2d(camera_transform, position) {
+            let snapped = (world / MACHINE_PARTS_GRID_SCALE).round() * MACHINE_PARTS_GRID_SCALE;
+            position = camera
+                .world_to_viewport(camera_transform, snapped.extend(0.0))
+                .unwrap_or(position);
+        }
+    }
+
+    let location = Location { target, position };
+    if position != shown_at || state.just_pressed(&Action::Place) {
+        let delta = position - shown_at;
+        let action = PointerAction::Move { delta };
+        pointer_input.write(PointerInput::new(CURSOR_POINTER, location.clone(), action));
+        cursor.shown_at = Some(position);
+    }
+    if state.just_pressed(&Action::Place) {
+        let action = PointerAction::Press(PointerButton::Primary);
+        pointer_input.write(PointerInput::new(CURSOR_POINTER, location.clone(), action));
+    }
+    if state.just_released(&Action::Place) {
+        let action = PointerAction::Release(PointerButton::Primary);
+        pointer_input.write(PointerInput::new(CURSOR_POINTER, location, action));
+    }
+    Ok(())
+}
+
+fn draw_cursor(
+    cursor: Res<GamepadCursor>,
+    node: Single<(&mut Node, &mut Visibility), With<CursorNode>>,
+) {
+    if !cursor.is_changed() {
+        return;
+    }
+    let (mut node, mut visibility) = node.into_inner();
+    match cursor.shown_at {
+        Some(position) => {
+            node.left = Px(position.x - CURSOR_SIZE / 2.0);
+            node.top = Px(position.y - CURSOR_SIZE / 2.0);
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+/// Picks the part after the one being placed, in the order of the buttons, going through the
+/// eraser after the last part.
+fn cycle_parts(
+    _: Trigger<OnPartCycle>,
+    physics_state: Res<State<PhysicsState>>,
+    mut picking_state: ResMut<PickingState>,
+    row: Query<&Children, With<MachinePartButtonNode>>,
+    buttons: Query<&MachinePartType, With<MachinePartButton>>,
+) {
+    if *physics_state.get() == PhysicsState::Running {
+        return;
+    }
+    let parts: Vec<_> = row
+        .iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|button| buttons.get(button).ok())
+        .collect();
+    let next = match &*picking_state {
+        PickingState::Placing(current) => parts
+            .iter()
+            .position(|part| part.name == current.name)
+            .map_or(0, |index| index + 1),
+        PickingState::Erasing | PickingState::None => 0,
+    };
+    *picking_state = match parts.get(next) {
+        Some(part) => PickingState::Placing((*part).clone()),
+        None => PickingState::Erasing,
+    };
+}
+
+// The UI sets `Interaction` from the mouse only, these do the same for the gamepad cursor.
+
+fn set_interaction(
+    pointer: PointerId,
+    target: Entity,
+    interaction: Interaction,
+    buttons: &mut Query<&mut Interaction, (With<Button>, Without<DisabledButton>)>,
+) {
+    if pointer != CURSOR_POINTER {
+        return;
+    }
+    if let Ok(mut current) = buttons.get_mut(target) {
+        // set even if unchanged, a second press has to trigger `OnPress` again
+        *current = interaction;
+    }
+}
+
+fn press_button(
+    trigger: Trigger<Pointer<Pressed>>,
+    mut buttons: Query<&mut Interaction, (With<Button>, Without<DisabledButton>)>,
+) {
+    set_interaction(
+        trigger.pointer_id,
+        trigger.target(),
+        Interaction::Pressed,
+        &mut buttons,
+    );
+}
+
+fn release_button(
+    trigger: Trigger<Pointer<Released>>,
+    mut buttons: Query<&mut Interaction, (With<Button>, Without<DisabledButton>)>,
+) {
+    set_interaction(
+        trigger.pointer_id,
+        trigger.target(),
+        Interaction::Hovered,
+        &mut buttons,
+    );
+}
+
+fn hover_button(
+    trigger: Trigger<Pointer<Over>>,
+    mut buttons: Query<&mut Interaction, (With<Button>, Without<DisabledButton>)>,
+) {
+    set_interaction(
+        trigger.pointer_id,
+        trigger.target(),
+        Interaction::Hovered,
+        &mut buttons,
+    );
+}
+
+fn leave_button(
+    trigger: Trigger<Pointer<Out>>,
+    mut buttons: Query<&mut Interaction, (With<Button>, Without<DisabledButton>)>,
+) {
+    set_interaction(
+        trigger.pointer_id,
+        trigger.target(),
+        Interaction::None,
+        &mut buttons,
+    );
+}
@@ -0,0 +1,77 @@
+//! Applies edits to `machine_parts.ron`, the images it generates colliders from and the level
+//! files to the level being played, without restarting the game.
+
+use crate::{game::physics::PhysicsState, prelude::*};
+use bevy::{platform::collections::HashMap, prelude::*};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            respawn_changed_parts.run_if(resource_exists_and_changed::<MachinePartConfigByType>),
+            reload_changed_level
+                .run_if(in_state(Screen::Gameplay).and(resource_exists::<LoadedLevel>)),
+        ),
+    );
+}
+
+/// Respawns every part whose definition changed where it was placed. Parts are compared by
+/// their debug output, which covers the generated colliders as well as the RON fields.
+fn respawn_changed_parts(
+    mut commands: Commands,
+    library: Res<MachinePartConfigByType>,
+    mut fingerprints: Local<HashMap<String, String>>,
+    parts: Query<(Entity, &MachinePartType, Has<IsInitialPart>), With<SpawnedMachinePart>>,
+    mut machine_part_request_writer: EventWriter<MachinePartRequest>,
+) {
+    let previous = std::mem::take(&mut *fingerprints);
+    *fingerprints = library
+        .0
+        .iter()
+        .map(|(name, config)| (name.clone(), format!("{config:?}")))
+        .collect();
+    // the first library isn't a change
+    if previous.is_empty() {
+        return;
+    }
+
+    let mut changed: Vec<_> = fingerprints
+        .iter()
+        .filter(|(name, fingerprint)| previous.get(*name) != Some(*fingerprint))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+    changed.sort();
+    info!("machine parts changed: {}", changed.join(", "));
+
+    for (entity, part_type, is_initial) in &parts {
+        if !changed.contains(&part_type.name.as_str()) {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        machine_part_request_writer.write(MachinePartRequest::SpawnMachinePart(
+            MachinePartSpawnRequest {
+                location: part_type.context.position,
+                part_type: part_type.clone(),
+                initial_part: is_initial,
+                free: true,
+            },
+        ));
+    }
+}
+
+/// Restarts the level when its file changes. Parts placed by the player are cleared with it.
+fn reload_changed_level(
+    mut events: EventReader<AssetEvent<LevelConfig>>,
+    mut loaded_level: ResMut<LoadedLevel>,
+    mut physics_state: ResMut<NextState<PhysicsState>>,
+) {
+    let id = loaded_level.0.id();
+    if events.read().any(|event| event.is_modified(id)) {
+        info!("level file changed, restarting it");
+        loaded_level.set_changed();
+        physics_state.set(PhysicsState::Paused);
+    }
+}
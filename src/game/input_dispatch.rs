@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::{game::settings::Rebinding, prelude::*};
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
 
@@ -12,7 +12,16 @@ pub fn plugin(app: &mut App) {
         .add_event::<OnPauseToggle>()
         .add_event::<OnClearModals>()
         .add_event::<OnDebugUiToggle>()
-        .add_systems(Update, trigger_input_dispatch);
+        .add_event::<OnColliderOverlayToggle>()
+        .add_event::<OnParticleViewCycle>()
+        .add_event::<OnSimulationSpeedChange>()
+        .add_event::<OnPhysicsStep>()
+        .add_event::<OnPartCycle>()
+        // the key being bound is for the keybinding screen only
+        .add_systems(
+            Update,
+            trigger_input_dispatch.run_if(not(resource_exists::<Rebinding>)),
+        );
 }
 
 #[derive(Event)]
@@ -32,9 +41,20 @@ pub struct OnPhysicsToggle;
 #[derive(Event)]
 pub struct OnDebugUiToggle;
 #[derive(Event)]
+pub struct OnColliderOverlayToggle;
+#[derive(Event)]
+pub struct OnParticleViewCycle;
+#[derive(Event)]
 pub struct OnRotate(pub i32);
+/// Moves the simulation speed this many steps up or down.
+#[derive(Event)]
+pub struct OnSimulationSpeedChange(pub i32);
+#[derive(Event)]
+pub struct OnPhysicsStep;
 #[derive(Event)]
 pub struct OnFlip;
+#[derive(Event)]
+pub struct OnPartCycle;
 
 fn trigger_input_dispatch(
     mut commands: Commands,
@@ -50,6 +70,12 @@ fn trigger_input_dispatch(
     if state.just_pressed(&Action::ToggleUiDebug) {
         commands.trigger(OnDebugUiToggle);
     }
+    if state.just_pressed(&Action::ToggleColliderOverlay) {
+        commands.trigger(OnColliderOverlayToggle);
+    }
+    if state.just_pressed(&Action::CycleParticleView) {
+        commands.trigger(OnParticleViewCycle);
+    }
     if state.just_pressed(&Action::Back) {
         match screen.get() {
             Screen::Splash | Screen::Title | Screen::Loading => {}
@@ -69,6 +95,19 @@ fn trigger_input_dispatch(
     if state.just_pressed(&Action::Flip) {
         commands.trigger(OnFlip);
     }
+    if state.just_pressed(&Action::CyclePart) {
+        commands.trigger(OnPartCycle);
+    }
+
+    if state.just_pressed(&Action::SlowDown) {
+        commands.trigger(OnSimulationSpeedChange(-1));
+    }
+    if state.just_pressed(&Action::SpeedUp) {
+        commands.trigger(OnSimulationSpeedChange(1));
+    }
+    if state.just_pressed(&Action::StepPhysics) {
+        commands.trigger(OnPhysicsStep);
+    }
 
     Ok(())
 }
@@ -0,0 +1,266 @@
+//! Heatmap colouring for particles and a tooltip with the exact contents of whatever is
+//! under the cursor.
+
+use crate::{
+    game::{
+        camera::MainCamera,
+        tea::{MIN_SENSOR_PARTICLES, Recipe, SensorReading, TeaSensor},
+    },
+    prelude::*,
+};
+use avian2d::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
+
+/// How close to a particle, in world units, the cursor has to be to inspect it.
+const HOVER_RADIUS: f32 = 6.0;
+/// Offset of the tooltip from the cursor, in logical pixels.
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<ParticleView>()
+        .add_systems(OnEnter(Screen::Gameplay), spawn_inspector_ui)
+        .add_systems(OnExit(Screen::Gameplay), reset_view)
+        .add_systems(
+            Update,
+            (update_view_label, update_tooltip).run_if(in_state(Screen::Gameplay)),
+        )
+        .add_observer(cycle_view);
+}
+
+/// What particle colours show.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleView {
+    /// Water mixed with tea and lightened by milk, how the drink looks.
+    #[default]
+    Contents,
+    Heat,
+    Tea,
+    Sugar,
+    Milk,
+}
+
+impl ParticleView {
+    const ALL: [Self; 5] = [
+        Self::Contents,
+        Self::Heat,
+        Self::Tea,
+        Self::Sugar,
+        Self::Milk,
+    ];
+
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|view| *view == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Localization key of the hint shown while this view is on.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Contents => "",
+            Self::Heat => "Showing heat ({key} to switch)",
+            Self::Tea => "Showing tea ({key} to switch)",
+            Self::Sugar => "Showing sugar ({key} to switch)",
+            Self::Milk => "Showing milk ({key} to switch)",
+        }
+    }
+
+    pub fn color(self, contents: &ParticleContents) -> Color {
+        let value = match self {
+            Self::Contents => {
+                return WATER
+                    .mix(&BREWED_TEA, contents.tea.min(1.0))
+                    .lighter(contents.milk.min(1.0));
+            }
+            Self::Heat => contents.heat,
+            Self::Tea => contents.tea,
+            Self::Sugar => contents.sugar,
+            Self::Milk => contents.milk,
+        };
+        // blue for nothing up to red for full
+        Color::hsl(240.0 * (1.0 - value.clamp(0.0, 1.0)), 0.9, 0.5)
+    }
+}
+
+#[derive(Component)]
+struct ParticleViewLabel;
+#[derive(Component)]
+struct InspectorTooltip;
+
+fn spawn_inspector_ui(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Particle View"),
+        StateScoped(Screen::Gameplay),
+        ParticleViewLabel,
+        Text::default(),
+        TextFont::from_font_size(16.0),
+        LocalizedText::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Px(8.0),
+            bottom: Px(8.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+    commands.spawn((
+        Name::new("Inspector Tooltip"),
+        StateScoped(Screen::Gameplay),
+        InspectorTooltip,
+        Text::default(),
+        TextFont::from_font_size(14.0),
+        LocalizedText::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(TRANSLUCENT),
+        GlobalZIndex(10),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+    ));
+}
+
+fn reset_view(mut view: ResMut<ParticleView>) {
+    *view = ParticleView::default();
+}
+
+fn cycle_view(_: Trigger<OnParticleViewCycle>, mut view: ResMut<ParticleView>) {
+    *view = view.next();
+}
+
+fn update_view_label(
+    view: Res<ParticleView>,
+    settings: Res<Settings>,
+    localization: Localization,
+    mut labels: Query<&mut Text, With<ParticleViewLabel>>,
+) {
+    let text = match *view {
+        ParticleView::Contents => String::new(),
+        view => {
+            let names = settings.keybinds.button_names(Action::CycleParticleView);
+            let keys = if names.is_empty() {
+                "-".to_string()
+            } else {
+                names.join(" / ")
+            };
+            localization.format(view.label(), &[("key", &keys)])
+        }
+    };
+    for mut label in &mut labels {
+        label.set_if_neq(Text(text.clone()));
+    }
+}
+
+/// Shows the closest particle under the cursor, or failing that the sensor under it. The
+/// heatmaps only change particle colours.
+fn update_tooltip(
+    ui_scale: Res<UiScale>,
+    localization: Localization,
+    spatial_query: SpatialQuery,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<MainCamera>>,
+    particles: Query<(&GlobalTransform, &Particle)>,
+    sensors: Query<(&TeaSensor, &SensorReading, Option<&Name>)>,
+    tooltip: Single<(&mut Text, &mut Node, &mut Visibility), With<InspectorTooltip>>,
+) {
+    let (mut text, mut node, mut visibility) = tooltip.into_inner();
+    let (camera, camera_transform) = *camera;
+    let Some((cursor, world)) = window.cursor_position().and_then(|cursor| {
+        let world = camera.viewport_to_world_2d(camera_transform, cursor).ok()?;
+        Some((cursor, world))
+    }) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let particle = particles
+        .iter()
+        .map(|(transform, particle)| (transform.translation().truncate().distance(world), particle))
+        .filter(|(distance, _)| *distance <= HOVER_RADIUS)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    let report = if let Some((_, particle)) = particle {
+        particle_report(&particle.contents, &localization)
+    } else if let Some((sensor, reading, name)) = spatial_query
+        .point_intersections(world, &SpatialQueryFilter::default())
+        .into_iter()
+        .find_map(|entity| sensors.get(entity).ok())
+    {
+        let name = name.map(Name::as_str).unwrap_or("Tea sensor");
+        sensor_report(&localization.get(name), &sensor.0, reading, &localization)
+    } else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    let position = (cursor + TOOLTIP_OFFSET) / ui_scale.0;
+    node.left = Px(position.x);
+    node.top = Px(position.y);
+    text.0 = report;
+    *visibility = Visibility::Inherited;
+}
+
+fn particle_report(contents: &ParticleContents, localization: &Localization) -> String {
+    let line = |ingredient: &str, value: f32| {
+        let args = [
+            ("ingredient", &*localization.get(ingredient)),
+            ("value", &amount(value)),
+        ];
+        localization.format("{ingredient} {value}", &args)
+    };
+    [
+        localization.format("heat {heat}", &[("heat", &amount(contents.heat))]),
+        line("tea", contents.tea),
+        line("sugar", contents.sugar),
+        line("milk", contents.milk),
+    ]
+    .join("\n")
+}
+
+/// The sensor average against every threshold its recipe checks, so it's clear which one
+/// is off.
+fn sensor_report(
+    name: &str,
+    recipe: &Recipe,
+    reading: &SensorReading,
+    localization: &Localization,
+) -> String {
+    let avg = reading.average;
+    let line = |ingredient: &str, value: f32, threshold: f32, wanted: bool| {
+        let key = match ((value > threshold) == wanted, wanted) {
+            (true, true) => "{ingredient} {value} (needs > {threshold}) ok",
+            (false, true) => "{ingredient} {value} (needs > {threshold}) not enough",
+            (true, false) => "{ingredient} {value} (needs <= {threshold}) ok",
+            (false, false) => "{ingredient} {value} (needs <= {threshold}) too much",
+        };
+        let args = [
+            ("ingredient", &*localization.get(ingredient)),
+            ("value", &amount(value)),
+            ("threshold", &amount(threshold)),
+        ];
+        localization.format(key, &args)
+    };
+    let count_key = if reading.count >= MIN_SENSOR_PARTICLES {
+        "particles {count} (needs {needed}) ok"
+    } else {
+        "particles {count} (needs {needed}) not enough"
+    };
+    let count_args = [
+        ("count", &*reading.count.to_string()),
+        ("needed", &MIN_SENSOR_PARTICLES.to_string()),
+    ];
+    [
+        name.to_string(),
+        localization.format(count_key, &count_args),
+        line("tea", avg.tea, TEA_THRESHOLD, true),
+        line("milk", avg.milk, MILK_THRESHOLD, recipe.milky),
+        line("sugar", avg.sugar, SUGAR_THRESHOLD, recipe.sweet),
+        localization.format("heat {heat}", &[("heat", &amount(avg.heat))]),
+    ]
+    .join("\n")
+}
+
+fn amount(value: f32) -> String {
+    format!("{value:.2}")
+}
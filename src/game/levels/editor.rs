@@ -1,5 +1,4 @@
 use crate::prelude::*;
-use avian2d::prelude::Gravity;
 use bevy::prelude::*;
 
 pub struct LevelEditor(pub String);
@@ -12,8 +11,7 @@ pub struct EditorLevel(pub Handle<LevelConfig>);
 
 impl Plugin for LevelEditor {
     fn build(&self, app: &mut App) {
-        let path = format!("assets/levels/{}.ron", self.0.clone());
-        app.insert_resource(LevelPath(path))
+        app.insert_resource(LevelPath(self.0.clone()))
             .add_systems(Update, save.run_if(resource_exists::<EditorLevel>))
             .add_systems(OnEnter(Screen::Gameplay), load);
     }
@@ -23,7 +21,6 @@ fn load(
     mut commands: Commands,
     level_path: Res<LevelPath>,
     mut level_configs: ResMut<Assets<LevelConfig>>,
-    cfg: Res<Config>,
 ) {
     let handle = if let Some(config) = std::fs::read_to_string(&level_path.0)
         .ok()
@@ -35,7 +32,6 @@ fn load(
     };
     commands.insert_resource(LoadedLevel(handle.clone()));
     commands.insert_resource(EditorLevel(handle));
-    commands.insert_resource(Gravity(Vec2::NEG_Y * 9.81 * cfg.physics.gravity));
 }
 
 fn save(
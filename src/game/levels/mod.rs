@@ -1,15 +1,16 @@
 use crate::prelude::*;
-use avian2d::prelude::*;
 use bevy::{
     asset::{AssetLoader, LoadContext, io::Reader},
     ecs::system::SystemState,
     prelude::*,
 };
-use bevy_common_assets::ron::RonAssetPlugin;
-use std::env;
 use thiserror::Error;
 
-use crate::{loading::LoadResource, screens::gameplay::ModifiedLevel};
+use crate::{
+    game::environment::EnvironmentOverrides,
+    loading::{LoadResource, RonAssetPlugin, validation::ContentIssue},
+    screens::gameplay::ModifiedLevel,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -42,8 +43,12 @@ pub fn plugin(app: &mut App) {
 
     let mut in_editor = false;
     #[cfg(feature = "dev")]
-    if let Some(path) = env::args().nth(1) {
-        app.add_plugins(editor::LevelEditor(path.to_string()));
+    if let Some(path) = app
+        .world()
+        .get_resource::<crate::cli::Args>()
+        .and_then(|args| args.editor.clone())
+    {
+        app.add_plugins(editor::LevelEditor(path));
         in_editor = true;
     }
 
@@ -69,8 +74,7 @@ fn reset_level(mut game_level: ResMut<NextState<GameLevel>>) {
     game_level.set(GameLevel::Start);
 }
 
-pub fn prepare_levels(cfg: Res<Config>, mut commands: Commands, level_list: Res<LevelList>) {
-    commands.insert_resource(Gravity(Vec2::NEG_Y * 9.81 * cfg.physics.gravity));
+pub fn prepare_levels(mut commands: Commands, level_list: Res<LevelList>) {
     commands.insert_resource(LoadedLevel(level_list.0[0].clone()));
 }
 
@@ -139,6 +143,13 @@ pub struct LevelConfig {
     pub zen_points: u32,
     pub available_machine_parts: Vec<String>,
     pub initial_machine_parts: Vec<MachinePartType>,
+    /// World-space area the camera can pan over and parts can be placed in.
+    /// Levels without bounds fit on one screen, see [`Config::screen_bounds`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Rect>,
+    /// Gravity, wind, temperatures and particle budget for this level only.
+    #[serde(default, skip_serializing_if = "EnvironmentOverrides::is_empty")]
+    pub environment: EnvironmentOverrides,
 }
 
 #[derive(Default)]
@@ -150,11 +161,13 @@ enum LevelListLoaderError {
     /// An [IO](std::io) Error
     #[error("Could not load asset: {0}")]
     Io(#[from] std::io::Error),
-    /// A [RON](ron) Error
-    #[error("Could not parse RON: {0}")]
-    RonSpannedError(#[from] ron::error::SpannedError),
+    /// A [RON](ron) Error pointed at the file and line
+    #[error(transparent)]
+    Content(#[from] ContentIssue),
     #[error(transparent)]
     LoadDirectError(#[from] bevy::asset::LoadDirectError),
+    #[error(transparent)]
+    AssetPath(#[from] bevy::asset::ParseAssetPathError),
 }
 
 impl AssetLoader for LevelListLoader {
@@ -169,15 +182,18 @@ impl AssetLoader for LevelListLoader {
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let levels = ron::de::from_bytes::<Vec<String>>(&bytes)?;
+        let levels = ron::de::from_bytes::<Vec<String>>(&bytes)
+            .map_err(|e| ContentIssue::from_ron(&load_context.path().to_string_lossy(), &e))?;
 
         let mut level_list = LevelList::default();
         for path in levels {
+            // relative to the list, so content packs can list their own files
+            let path = load_context.asset_path().resolve_embed(&path)?;
             level_list.0.push(
                 load_context
                     .loader()
                     .with_static_type()
-                    .load::<LevelConfig>(path.clone()),
+                    .load::<LevelConfig>(path),
             );
         }
 
@@ -0,0 +1,270 @@
+use crate::{
+    prelude::*,
+    util::image_geometry::{RgbaBuffer, rotation_colliders},
+};
+use avian2d::parry::{
+    math::{Isometry, Point, Vector},
+    shape::{Compound, SharedShape},
+};
+use bevy::{asset::RenderAssetUsages, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use thiserror::Error;
+
+/// Path of the baked collider cache, relative to the asset root.
+pub const COLLIDER_CACHE_PATH: &str = "machine_parts.colliders.ron";
+
+/// Colliders generated offline by `bake-colliders`, keyed by [`collider_cache_key`].
+///
+/// Entries are only ever looked up by the hash of the image they were generated from,
+/// so a stale entry is simply never hit and the loader falls back to live generation.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ColliderCache(pub BTreeMap<String, Vec<Vec<BakedCompound>>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BakedCompound(pub Vec<BakedConvex>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BakedConvex {
+    pub position: Vec2,
+    pub angle: f32,
+    pub points: Vec<Vec2>,
+}
+
+impl ColliderCache {
+    /// Colliders for every rotation row of `image`, from the cache if possible.
+    pub fn rotation_colliders(
+        &self,
+        image: &Image,
+        rotations: u32,
+        settings: &ColliderGenSettings,
+    ) -> Vec<Vec<Compound>> {
+        let key = collider_cache_key(image, rotations, settings);
+        if let Some(baked) = self.0.get(&key) {
+            let rows: Option<Vec<Vec<Compound>>> = baked
+                .iter()
+                .map(|row| row.iter().map(BakedCompound::to_compound).collect())
+                .collect();
+            if let Some(rows) = rows {
+                return rows;
+            }
+            warn!("collider cache entry {key} has invalid shapes, generating colliders");
+        } else {
+            debug!("collider cache miss for {key}, generating colliders");
+        }
+        rotation_colliders(&RgbaBuffer::from_image(image), rotations, settings)
+    }
+
+    /// Generates the colliders for `image` and adds them, unless they are already cached.
+    /// Returns whether anything was added.
+    pub fn bake(&mut self, image: &Image, rotations: u32, settings: &ColliderGenSettings) -> bool {
+        let key = collider_cache_key(image, rotations, settings);
+        if self.0.contains_key(&key) {
+            return false;
+        }
+        let baked = rotation_colliders(&RgbaBuffer::from_image(image), rotations, settings)
+            .iter()
+            .map(|row| row.iter().map(BakedCompound::from_compound).collect())
+            .collect();
+        self.0.insert(key, baked);
+        true
+    }
+}
+
+impl BakedCompound {
+    fn from_compound(compound: &Compound) -> Self {
+        let parts = compound
+            .shapes()
+            .iter()
+            .filter_map(|(isometry, shape)| {
+                let polygon = shape.as_convex_polygon()?;
+                Some(BakedConvex {
+                    position: Vec2::new(isometry.translation.x, isometry.translation.y),
+                    angle: isometry.rotation.angle(),
+                    points: polygon
+                        .points()
+                        .iter()
+                        .map(|p| Vec2::new(p.x, p.y))
+                        .collect(),
+                })
+            })
+            .collect();
+        Self(parts)
+    }
+
+    /// `None` if any of the parts is no longer a valid convex shape, a compound with pieces
+    /// missing would let particles through.
+    fn to_compound(&self) -> Option<Compound> {
+        let shapes: Vec<_> = self
+            .0
+            .iter()
+            .map(|part| {
+                let points = part.points.iter().map(|p| Point::new(p.x, p.y)).collect();
+                let shape = SharedShape::convex_polyline(points)?;
+                let isometry =
+                    Isometry::new(Vector::new(part.position.x, part.position.y), part.angle);
+                Some((isometry, shape))
+            })
+            .collect::<Option<_>>()?;
+        (!shapes.is_empty()).then(|| Compound::new(shapes))
+    }
+}
+
+/// FNV-1a hash of the pixel data, the row count and the generation settings, hex encoded.
+pub fn collider_cache_key(image: &Image, rotations: u32, settings: &ColliderGenSettings) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let size = image.size();
+    let header = [size.x, size.y, rotations].map(u32::to_le_bytes);
+    let settings = ron::to_string(settings).unwrap_or_default();
+    let data = image.data.as_deref().unwrap_or_default();
+    for byte in header
+        .iter()
+        .flatten()
+        .chain(settings.as_bytes())
+        .chain(data)
+    {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ColliderBakeError {
+    #[error("Could not read {0}: {1}")]
+    Io(String, std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] MachinePartsParseError),
+    #[error("Could not write RON: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("Could not decode {0}: {1}")]
+    Image(String, image::ImageError),
+}
+
+/// Generates colliders for every collider subassembly in `machine_parts.ron` and writes
+/// them to [`COLLIDER_CACHE_PATH`]. Returns the number of baked images.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn bake_colliders(asset_root: &Path) -> Result<usize, ColliderBakeError> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|e| ColliderBakeError::Io(path.display().to_string(), e))
+    };
+
+    let bytes = read(&asset_root.join("machine_parts.ron"))?;
+    let library = parse_machine_parts(&bytes)?;
+
+    let mut cache = ColliderCache::default();
+    for (name, config) in library.0.iter() {
+        let rotations = config.texture_info.rotations;
+        for (path, settings) in config
+            .subassemblies
+            .iter()
+            .filter_map(SubAssembly::collider_source)
+        {
+            let bytes = read(&asset_root.join(path))?;
+            let decoded = image::load_from_memory(&bytes)
+                .map_err(|e| ColliderBakeError::Image(path.to_string(), e))?;
+            let image = Image::from_dynamic(decoded, true, RenderAssetUsages::default());
+
+            if cache.bake(&image, rotations, settings) {
+                println!("baked {name}: {path}");
+            }
+        }
+    }
+
+    let out = asset_root.join(COLLIDER_CACHE_PATH);
+    let text = ron::ser::to_string_pretty(&cache, ron::ser::PrettyConfig::default())?;
+    std::fs::write(&out, text).map_err(|e| ColliderBakeError::Io(out.display().to_string(), e))?;
+    Ok(cache.0.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16x16 image, solid except for a notch, so the collider is more than one convex piece.
+    fn image(notch: u8) -> Image {
+        let pixels = image::RgbaImage::from_fn(16, 16, |x, y| {
+            let notched = (6..10).contains(&x) && y < notch as u32;
+            image::Rgba(if notched { [0; 4] } else { [255; 4] })
+        });
+        Image::from_dynamic(pixels.into(), true, RenderAssetUsages::default())
+    }
+
+    fn points(rows: &[Vec<Compound>]) -> Vec<Vec<Vec<Vec2>>> {
+        rows.iter()
+            .flatten()
+            .map(|compound| {
+                BakedCompound::from_compound(compound)
+                    .0
+                    .into_iter()
+                    .map(|part| {
+                        let rotation = Rot2::radians(part.angle);
+                        part.points
+                            .into_iter()
+                            .map(|point| part.position + rotation * point)
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn key_changes_with_pixels_rotations_and_settings() {
+        let settings = ColliderGenSettings::default();
+        let key = collider_cache_key(&image(8), 1, &settings);
+        assert_eq!(key, collider_cache_key(&image(8), 1, &settings));
+        assert_ne!(key, collider_cache_key(&image(9), 1, &settings));
+        assert_ne!(key, collider_cache_key(&image(8), 2, &settings));
+        let hull = ColliderGenSettings {
+            single_convex_hull: true,
+            ..default()
+        };
+        assert_ne!(key, collider_cache_key(&image(8), 1, &hull));
+    }
+
+    #[test]
+    fn baked_colliders_survive_a_round_trip() {
+        let (image, settings) = (image(8), ColliderGenSettings::default());
+        let mut cache = ColliderCache::default();
+        assert!(cache.bake(&image, 2, &settings));
+        assert!(!cache.bake(&image, 2, &settings));
+
+        let text = ron::ser::to_string_pretty(&cache, default()).unwrap();
+        let loaded: ColliderCache = ron::de::from_str(&text).unwrap();
+        assert!(
+            loaded
+                .0
+                .contains_key(&collider_cache_key(&image, 2, &settings))
+        );
+
+        let generated = rotation_colliders(&RgbaBuffer::from_image(&image), 2, &settings);
+        let (generated, cached) = (
+            points(&generated),
+            points(&loaded.rotation_colliders(&image, 2, &settings)),
+        );
+        assert_eq!(generated.len(), cached.len());
+        for (generated, cached) in generated.iter().flatten().zip(cached.iter().flatten()) {
+            assert_eq!(generated.len(), cached.len());
+            for (a, b) in generated.iter().zip(cached) {
+                assert!(a.distance(*b) < 1e-4, "{a} != {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_entries_fall_back_to_generating() {
+        let (image, settings) = (image(8), ColliderGenSettings::default());
+        let mut cache = ColliderCache::default();
+        cache.bake(&image, 1, &settings);
+        let entry = cache.0.values_mut().next().unwrap();
+        entry[0][0].0[0].points.clear();
+
+        let generated = rotation_colliders(&RgbaBuffer::from_image(&image), 1, &settings);
+        assert_eq!(
+            points(&cache.rotation_colliders(&image, 1, &settings)),
+            points(&generated)
+        );
+    }
+}
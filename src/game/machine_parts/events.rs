@@ -4,7 +4,8 @@ use bevy::prelude::*;
 #[derive(Event, Debug)]
 pub enum MachinePartRequest {
     SpawnMachinePart(MachinePartSpawnRequest),
-    WhateverOtherRequests,
+    /// Erases this spawned part, refunding its cost.
+    EraseMachinePart(Entity),
 }
 
 #[derive(Default, Debug)]
@@ -114,9 +114,13 @@ fn do_flow_fields(
                         image.get_color_at(pixel_pos.x as u32, pixel_pos.y as u32 + v_offset)
                     {
                         let rgba = color.to_srgba();
-                        let mut new_force =
+                        let local_force =
                             Vec2::new((rgba.red - 0.5) * 2.0, (rgba.green - 0.5) * 2.0)
                                 * rgba.alpha;
+                        // free rotation parts turn the texture with the transform, so the
+                        // sampled vector is in local space too
+                        let mut new_force =
+                            (flow_transform.rotation() * local_force.extend(0.0)).truncate();
 
                         // Adjust vertical force multiplier based on sign
                         new_force.y *= if new_force.y > 0.0 { 2.0 } else { 0.5 };
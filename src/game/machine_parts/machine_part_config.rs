@@ -1,12 +1,14 @@
 use crate::{
     game::{
+        ParticleLayer,
         heat::HeatSource,
         machine_parts::{
             animator::{BasicSpriteAnimationController, SpriteFrames},
             particle_vessel::ParticleVessel,
         },
+        sound::{PartSound, SoundTrigger},
         tea::{Recipe, Tea, TeaSensor},
-        tea_particles::TeaParticleVessel, ParticleLayer,
+        tea_particles::TeaParticleVessel,
     },
     prelude::*,
 };
@@ -15,10 +17,14 @@ use avian2d::{
     prelude::*,
 };
 use bevy::prelude::*;
+use bevy_seedling::sample::Sample;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, Reflect)]
 pub struct MachinePartConfig {
+    /// Localization key of the name players see, the part's name when left out.
+    #[serde(default)]
+    pub display_name: Option<String>,
     pub cost: u32,
     pub is_dynamic: bool,
     #[serde(default)]
@@ -52,6 +58,8 @@ pub enum SubAssembly {
         #[serde(default)]
         offset: Vec2,
         mesh_image_path: String,
+        #[serde(default)]
+        collider_settings: ColliderGenSettings,
         #[serde(skip)]
         #[reflect(ignore)]
         colliders: Vec<Vec<Compound>>,
@@ -60,6 +68,8 @@ pub enum SubAssembly {
         #[serde(default)]
         offset: Vec2,
         mesh_image_path: String,
+        #[serde(default)]
+        collider_settings: ColliderGenSettings,
         #[serde(skip)]
         #[reflect(ignore)]
         colliders: Vec<Vec<Compound>>,
@@ -74,6 +84,8 @@ pub enum SubAssembly {
         #[serde(default)]
         offset: Vec2,
         mesh_image_path: String,
+        #[serde(default)]
+        collider_settings: ColliderGenSettings,
         #[serde(skip)]
         #[reflect(ignore)]
         colliders: Vec<Vec<Compound>>,
@@ -82,6 +94,8 @@ pub enum SubAssembly {
         #[serde(default)]
         offset: Vec2,
         mesh_image_path: String,
+        #[serde(default)]
+        collider_settings: ColliderGenSettings,
         #[serde(skip)]
         #[reflect(ignore)]
         colliders: Vec<Vec<Compound>>,
@@ -141,6 +155,30 @@ pub enum SubAssembly {
         offset: Vec2,
         radius: f32,
     },
+    /// Hum and hit sounds of the part, paths are relative to the assets folder.
+    Sound {
+        #[serde(default)]
+        offset: Vec2,
+        /// Loops for as long as the part exists, or only while running with `while_running`.
+        #[serde(default)]
+        ambient_path: Option<String>,
+        #[serde(skip)]
+        ambient: Option<Handle<Sample>>,
+        /// Played when the simulation starts or when something hits the part, see `play_on`.
+        #[serde(default)]
+        one_shot_path: Option<String>,
+        #[serde(skip)]
+        one_shot: Option<Handle<Sample>>,
+        #[serde(default)]
+        play_on: SoundTrigger,
+        #[serde(default = "default_sound_volume")]
+        volume: f32,
+        /// Playback speed is picked from this range every time a sample starts.
+        #[serde(default = "default_sound_pitch")]
+        pitch: (f32, f32),
+        #[serde(default)]
+        while_running: bool,
+    },
     Tea {
         #[serde(default)]
         offset: Vec2,
@@ -150,6 +188,8 @@ pub enum SubAssembly {
         #[serde(default)]
         offset: Vec2,
         mesh_image_path: String,
+        #[serde(default)]
+        collider_settings: ColliderGenSettings,
         #[serde(skip)]
         #[reflect(ignore)]
         colliders: Vec<Vec<Compound>>,
@@ -173,6 +213,121 @@ pub enum SubAssembly {
     },
 }
 
+fn default_sound_volume() -> f32 {
+    1.0
+}
+
+fn default_sound_pitch() -> (f32, f32) {
+    (1.0, 1.0)
+}
+
+impl SubAssembly {
+    /// Asset paths the subassembly loads, so they can be made relative to the file it is
+    /// defined in.
+    pub fn paths_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            SubAssembly::Collider {
+                mesh_image_path, ..
+            }
+            | SubAssembly::ConveyorBelt {
+                mesh_image_path, ..
+            }
+            | SubAssembly::FluidFilter {
+                mesh_image_path, ..
+            }
+            | SubAssembly::FluidFilterButton {
+                mesh_image_path, ..
+            } => vec![mesh_image_path],
+            SubAssembly::TeaSensor {
+                mesh_image_path,
+                icon_asset_path,
+                ..
+            } => vec![mesh_image_path, icon_asset_path],
+            SubAssembly::Sprite {
+                sprite_asset_path, ..
+            } => vec![sprite_asset_path],
+            SubAssembly::ParticleVessel { texture_path, .. } => vec![texture_path],
+            SubAssembly::TeaParticleVessel {
+                texture_path,
+                particle_texture_path,
+                ..
+            } => vec![texture_path, particle_texture_path],
+            SubAssembly::FlowField {
+                flow_texture_path, ..
+            } => vec![flow_texture_path],
+            SubAssembly::Sound {
+                ambient_path,
+                one_shot_path,
+                ..
+            } => ambient_path.iter_mut().chain(one_shot_path).collect(),
+            SubAssembly::RedBall
+            | SubAssembly::CircleCollider { .. }
+            | SubAssembly::HeatSource { .. }
+            | SubAssembly::Tea { .. } => vec![],
+        }
+    }
+
+    /// Image the subassembly generates its colliders from and how, if it has any.
+    pub fn collider_source(&self) -> Option<(&str, &ColliderGenSettings)> {
+        match self {
+            SubAssembly::Collider {
+                mesh_image_path,
+                collider_settings,
+                ..
+            }
+            | SubAssembly::ConveyorBelt {
+                mesh_image_path,
+                collider_settings,
+                ..
+            }
+            | SubAssembly::FluidFilter {
+                mesh_image_path,
+                collider_settings,
+                ..
+            }
+            | SubAssembly::FluidFilterButton {
+                mesh_image_path,
+                collider_settings,
+                ..
+            }
+            | SubAssembly::TeaSensor {
+                mesh_image_path,
+                collider_settings,
+                ..
+            } => Some((mesh_image_path, collider_settings)),
+            _ => None,
+        }
+    }
+}
+
+/// Tuning for the image to collider conversion, for when the defaults produce a bad shape.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct ColliderGenSettings {
+    /// Pixels with at least this alpha are solid.
+    pub alpha_threshold: f32,
+    /// How far from straight (1 - cos of the angle) an outline corner can be before it is kept.
+    pub simplify_epsilon: f64,
+    /// Overrides the VHACD voxel resolution.
+    pub vhacd_resolution: Option<u32>,
+    /// Overrides the maximum concavity VHACD accepts in a piece.
+    pub vhacd_concavity: Option<f32>,
+    /// Skip the decomposition and use the convex hull of each outline.
+    pub single_convex_hull: bool,
+}
+
+impl Default for ColliderGenSettings {
+    fn default() -> Self {
+        Self {
+            alpha_threshold: 1.0,
+            simplify_epsilon: 0.0005,
+            vhacd_resolution: None,
+            vhacd_concavity: None,
+            single_convex_hull: false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Reflect, Default)]
 pub struct PartIcon {
     pub path: String,
@@ -188,6 +343,17 @@ pub struct TextureInfo {
     pub rotations: u32,
     #[serde(default)]
     pub flippable: bool, //if flippable the 2nd half of the rotations will only be accessible while flipped
+    /// Rotate the single sprite row (and its colliders and flow field) with the transform
+    /// instead of picking a pre-drawn rotation row.
+    #[serde(default)]
+    pub free_rotation: bool,
+    /// Angle step in degrees used by free rotation.
+    #[serde(default = "default_rotation_snap_deg")]
+    pub rotation_snap_deg: f32,
+}
+
+fn default_rotation_snap_deg() -> f32 {
+    15.0
 }
 
 impl Default for TextureInfo {
@@ -196,10 +362,24 @@ impl Default for TextureInfo {
             frames: SpriteFrames::One,
             rotations: 1,
             flippable: false,
+            free_rotation: false,
+            rotation_snap_deg: default_rotation_snap_deg(),
         }
     }
 }
 
+impl TextureInfo {
+    /// Snaps an angle in degrees to the configured step and wraps it into `[0, 360)`.
+    pub fn snapped_angle(&self, angle_deg: f32) -> f32 {
+        let snapped = if self.rotation_snap_deg > 0.0 {
+            (angle_deg / self.rotation_snap_deg).round() * self.rotation_snap_deg
+        } else {
+            angle_deg
+        };
+        snapped.rem_euclid(360.0)
+    }
+}
+
 #[derive(Component)]
 pub struct MachineSprite;
 
@@ -214,15 +394,33 @@ pub struct PlacementContext {
     pub position: Vec3,
     pub rotation_index: u32,
     pub flipped: bool,
+    /// Counter-clockwise angle in degrees, only used by parts with `free_rotation`.
+    #[serde(default)]
+    pub angle_deg: f32,
+}
+
+impl PlacementContext {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_z(self.angle_deg.to_radians())
+    }
 }
 
 #[derive(Component)]
 pub struct SpawnedMachinePart;
 
+/// Marks colliders that were generated from a mesh image.
+#[derive(Component)]
+pub struct GeneratedCollider;
+
 #[derive(Component)]
 pub struct RedBall;
 
 impl MachinePartConfig {
+    /// The key to look up in `locales.ron` for the part called `name`.
+    pub fn display_key<'a>(&'a self, name: &'a str) -> &'a str {
+        self.display_name.as_deref().unwrap_or(name)
+    }
+
     pub fn spawn_sprites(&self, sprite_index: u32, mut commands: EntityCommands) {
         commands.with_children(|parent| {
             for subassembly in &self.subassemblies {
@@ -260,8 +458,6 @@ impl MachinePartConfig {
         &self,
         part_type: MachinePartType,
         commands: &mut Commands,
-        sounds: &Res<AudioSources>,
-        settings: &Res<Settings>,
         #[cfg(debug_assertions)] meshes: &mut ResMut<Assets<Mesh>>,
         #[cfg(debug_assertions)] materials: &mut ResMut<Assets<ColorMaterial>>,
     ) -> Entity {
@@ -271,7 +467,7 @@ impl MachinePartConfig {
         let mut part = commands.spawn((
             SpawnedMachinePart,
             LevelObject,
-            Transform::from_translation(context.position),
+            Transform::from_translation(context.position).with_rotation(context.rotation()),
             part_type.clone(),
             if self.is_dynamic {
                 RigidBody::Dynamic
@@ -339,6 +535,7 @@ impl MachinePartConfig {
                                 parent.spawn((
                                     Transform::from_xyz(offset.x, offset.y, 0.0),
                                     Collider::from(SharedShape::new(collider.clone())),
+                                    GeneratedCollider,
                                 ));
                             }
                         }
@@ -362,7 +559,10 @@ impl MachinePartConfig {
                                 parent.spawn((
                                     Transform::from_xyz(offset.x, offset.y, 0.0),
                                     Collider::from(SharedShape::new(collider.clone())),
-                                    crate::game::conveyor_belts::ConveyorBelt { speed: actual_speed },
+                                    GeneratedCollider,
+                                    crate::game::conveyor_belts::ConveyorBelt {
+                                        speed: actual_speed,
+                                    },
                                 ));
                             }
                         }
@@ -376,11 +576,12 @@ impl MachinePartConfig {
                                 parent.spawn((
                                     Transform::from_xyz(offset.x, offset.y, 0.0),
                                     Collider::from(SharedShape::new(collider.clone())),
+                                    GeneratedCollider,
                                     FluidFilter,
                                     CollisionLayers::new(
                                         ParticleLayer::Default,
                                         [ParticleLayer::Default, ParticleLayer::TeaLeaves],
-                                    )
+                                    ),
                                 ));
                             }
                         }
@@ -394,6 +595,7 @@ impl MachinePartConfig {
                                 parent.spawn((
                                     Transform::from_xyz(offset.x, offset.y, 0.0),
                                     Collider::from(SharedShape::new(collider.clone())),
+                                    GeneratedCollider,
                                     FluidFilterButton::default(),
                                     Sensor,
                                 ));
@@ -467,7 +669,6 @@ impl MachinePartConfig {
                     }
                     SubAssembly::HeatSource { offset, radius } => {
                         parent.spawn((
-                            sfx_looping(sounds.stove_looping.clone(), settings.sfx()),
                             Transform::from_xyz(offset.x, offset.y, 0.0),
                             HeatSource,
                             Collider::circle(*radius),
@@ -479,6 +680,28 @@ impl MachinePartConfig {
                             Pickable::IGNORE,
                         ));
                     }
+                    SubAssembly::Sound {
+                        offset,
+                        ambient,
+                        one_shot,
+                        play_on,
+                        volume,
+                        pitch,
+                        while_running,
+                        ..
+                    } => {
+                        parent.spawn((
+                            Transform::from_xyz(offset.x, offset.y, 0.0),
+                            PartSound {
+                                ambient: ambient.clone(),
+                                one_shot: one_shot.clone(),
+                                play_on: *play_on,
+                                volume: *volume,
+                                pitch: *pitch,
+                                while_running: *while_running,
+                            },
+                        ));
+                    }
                     SubAssembly::Tea { offset, radius } => {
                         parent.spawn((
                             Transform::from_xyz(offset.x, offset.y, 0.0),
@@ -496,12 +719,14 @@ impl MachinePartConfig {
                         ..
                     } => {
                         if let Some(collider_set) = colliders.get(context.rotation_index as usize) {
+                            // the loader rejects sensor images that aren't a single shape
                             assert!(collider_set.len() == 1);
                             for collider in collider_set {
                                 parent.spawn((
                                     Name::new(name.clone()),
                                     Transform::from_xyz(offset.x, offset.y, 0.0),
                                     Collider::from(SharedShape::new(collider.clone())),
+                                    GeneratedCollider,
                                     Sensor,
                                     Pickable::IGNORE,
                                     TeaSensor(*recipe, icon.clone()),
@@ -552,24 +777,25 @@ fn handle_erase_click(
     trigger: Trigger<Pointer<Click>>,
     mut commands: Commands,
     picking_state: Res<PickingState>,
-    part_type: Query<&MachinePartType>,
-    machine_part_config_by_type: Res<MachinePartConfigByType>,
-    mut available_zen_points: ResMut<AvailableZenPoints>,
+    parts: Query<(), With<MachinePartType>>,
     initial_part: Query<&IsInitialPart>,
+    transforms: Query<&GlobalTransform>,
     sounds: Res<AudioSources>,
     settings: Res<Settings>,
     editor_mode: Res<EditorMode>,
+    mut machine_part_request_writer: EventWriter<MachinePartRequest>,
 ) {
     if *picking_state == PickingState::Erasing {
         if editor_mode.0 || !initial_part.contains(trigger.target()) {
-            if let Ok(ty) = part_type.get(trigger.target()) {
-                if let Some(part_config) = machine_part_config_by_type.0.get(&ty.name) {
-                    let source = sounds.cancel_piece.clone();
-                    commands.spawn(sfx(source, settings.sfx()));
+            if parts.contains(trigger.target()) {
+                let source = sounds.cancel_piece.clone();
+                let at = transforms
+                    .get(trigger.target())
+                    .map_or(Vec3::ZERO, |t| t.translation());
+                commands.spawn(spatial_sfx(source, settings.sfx(), at));
 
-                    available_zen_points.refund(part_config.cost);
-                    commands.entity(trigger.target()).despawn();
-                }
+                machine_part_request_writer
+                    .write(MachinePartRequest::EraseMachinePart(trigger.target()));
             }
         }
     }
@@ -1,14 +1,21 @@
-use crate::{loading::LoadResource, prelude::*};
-use avian2d::{parry::shape::Compound, prelude::*};
+use crate::{
+    game::machine_parts::collider_cache::{COLLIDER_CACHE_PATH, ColliderCache},
+    loading::{LoadResource, validation::ContentIssue},
+    prelude::*,
+};
+use avian2d::prelude::*;
 use bevy::{
-    asset::{AssetLoader, LoadContext, io::Reader},
+    asset::{AssetLoader, LoadContext, ParseAssetPathError, io::Reader},
     platform::collections::HashMap,
     prelude::*,
 };
-use geo::{BooleanOps, Coord, CoordsIter, LineString, MultiPolygon, Vector2DOps};
-use itertools::Itertools as _;
-use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use bevy_seedling::sample::Sample;
+use ron::extensions::Extensions;
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{MapAccess, Visitor},
+};
+use std::{fmt, mem::discriminant};
 use thiserror::Error;
 
 #[derive(Resource, Asset, Clone, Debug, Reflect, Serialize, Deserialize)]
@@ -24,7 +31,177 @@ impl Plugin for MachinePartConfigByTypePlugin {
 }
 
 #[derive(Default)]
-struct MachinePartConfigByTypeLoader;
+pub struct MachinePartConfigByTypeLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum MachinePartsParseError {
+    /// A [RON](ron) Error
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("\"{part}\" extends unknown part \"{parent}\"")]
+    UnknownParent { part: String, parent: String },
+    #[error("inheritance cycle: {0}")]
+    InheritanceCycle(String),
+    #[error("\"{part}\" is missing `{field}` and does not inherit it")]
+    MissingField { part: String, field: &'static str },
+    #[error("\"{0}\" is defined more than once")]
+    DuplicatePart(String),
+}
+
+/// `machine_parts.ron` as written, `({ "Part": (..), .. })`.
+#[derive(Deserialize)]
+struct PartsFile(Definitions);
+
+/// The parts in file order. A plain map would keep one of two parts with the same name
+/// without saying so.
+struct Definitions(Vec<(String, MachinePartDefinition)>);
+
+impl<'de> Deserialize<'de> for Definitions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DefinitionsVisitor;
+
+        impl<'de> Visitor<'de> for DefinitionsVisitor {
+            type Value = Definitions;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of part names to parts")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Definitions, A::Error> {
+                let mut parts = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    parts.push(entry);
+                }
+                Ok(Definitions(parts))
+            }
+        }
+
+        deserializer.deserialize_map(DefinitionsVisitor)
+    }
+}
+
+/// A part as written in `machine_parts.ron`, before `extends` is resolved.
+///
+/// Every field left out is taken from the parent. Subassemblies replace the parent's
+/// subassembly of the same kind at the same position among that kind (the second `Sprite`
+/// replaces the second `Sprite`), the rest are appended.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MachinePartDefinition {
+    extends: Option<String>,
+    /// Templates only exist to be extended and are left out of the library.
+    template: bool,
+    /// Names the part itself, so it isn't inherited.
+    display_name: Option<String>,
+    cost: Option<u32>,
+    is_dynamic: Option<bool>,
+    icon: Option<PartIcon>,
+    texture_info: Option<TextureInfo>,
+    subassemblies: Vec<SubAssembly>,
+}
+
+impl MachinePartDefinition {
+    fn inherit(self, child: MachinePartDefinition) -> MachinePartDefinition {
+        let mut subassemblies = self.subassemblies;
+        let mut seen = HashMap::new();
+        for subassembly in child.subassemblies {
+            let kind = discriminant(&subassembly);
+            let ordinal = seen.entry(kind).or_insert(0usize);
+            let replaced = subassemblies
+                .iter_mut()
+                .filter(|s| discriminant(&**s) == kind)
+                .nth(*ordinal);
+            *ordinal += 1;
+            match replaced {
+                Some(slot) => *slot = subassembly,
+                None => subassemblies.push(subassembly),
+            }
+        }
+
+        MachinePartDefinition {
+            extends: child.extends,
+            template: child.template,
+            display_name: child.display_name,
+            cost: child.cost.or(self.cost),
+            is_dynamic: child.is_dynamic.or(self.is_dynamic),
+            icon: child.icon.or(self.icon),
+            texture_info: child.texture_info.or(self.texture_info),
+            subassemblies,
+        }
+    }
+}
+
+/// Parses `machine_parts.ron` and resolves `extends` chains. Images are not loaded.
+pub fn parse_machine_parts(
+    bytes: &[u8],
+) -> Result<MachinePartConfigByType, MachinePartsParseError> {
+    let options = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+    let PartsFile(Definitions(parts)) = options.from_bytes(bytes)?;
+
+    let mut definitions = HashMap::new();
+    for (name, definition) in &parts {
+        if definitions.insert(name.as_str(), definition).is_some() {
+            return Err(MachinePartsParseError::DuplicatePart(name.clone()));
+        }
+    }
+
+    let mut library = HashMap::new();
+    // templates are walked too, a cycle among them is still a mistake
+    for (name, definition) in &parts {
+        // walk up to the root, then apply the chain back down
+        let mut chain = vec![name.as_str()];
+        let mut current = definition;
+        while let Some(parent) = &current.extends {
+            if chain.contains(&parent.as_str()) {
+                chain.push(parent);
+                return Err(MachinePartsParseError::InheritanceCycle(chain.join(" -> ")));
+            }
+            current = definitions.get(parent.as_str()).copied().ok_or_else(|| {
+                MachinePartsParseError::UnknownParent {
+                    part: chain[chain.len() - 1].to_string(),
+                    parent: parent.clone(),
+                }
+            })?;
+            chain.push(parent);
+        }
+        if definition.template {
+            continue;
+        }
+        let resolved = chain
+            .iter()
+            .rev()
+            .map(|name| definitions[*name].clone())
+            .reduce(MachinePartDefinition::inherit)
+            .unwrap_or_default();
+
+        let missing = |field| MachinePartsParseError::MissingField {
+            part: name.clone(),
+            field,
+        };
+        let mut config = MachinePartConfig {
+            display_name: resolved.display_name,
+            cost: resolved.cost.ok_or_else(|| missing("cost"))?,
+            is_dynamic: resolved.is_dynamic.ok_or_else(|| missing("is_dynamic"))?,
+            icon: resolved.icon.unwrap_or_default(),
+            texture_info: resolved.texture_info.unwrap_or_default(),
+            subassemblies: resolved.subassemblies,
+        };
+
+        // free rotation works on a single row, every other angle comes from the transform
+        let texture_info = &mut config.texture_info;
+        if texture_info.free_rotation && texture_info.rotations != 1 {
+            warn!(
+                "{name}: free_rotation expects a single sprite row, ignoring rotations: {}",
+                texture_info.rotations
+            );
+            texture_info.rotations = 1;
+        }
+        library.insert(name.clone(), config);
+    }
+
+    Ok(MachinePartConfigByType(library))
+}
 
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -32,11 +209,13 @@ enum MachinePartConfigByTypeLoaderError {
     /// An [IO](std::io) Error
     #[error("Could not load asset: {0}")]
     Io(#[from] std::io::Error),
-    /// A [RON](ron) Error
-    #[error("Could not parse RON: {0}")]
-    RonSpannedError(#[from] ron::error::SpannedError),
+    /// A parse error pointed at the file and line
+    #[error(transparent)]
+    Content(#[from] ContentIssue),
     #[error(transparent)]
     LoadDirectError(#[from] bevy::asset::LoadDirectError),
+    #[error(transparent)]
+    AssetPath(#[from] ParseAssetPathError),
 }
 
 impl AssetLoader for MachinePartConfigByTypeLoader {
@@ -51,13 +230,38 @@ impl AssetLoader for MachinePartConfigByTypeLoader {
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let mut library = ron::de::from_bytes::<MachinePartConfigByType>(&bytes)?;
-        for MachinePartConfig {
-            subassemblies,
-            icon,
-            texture_info,
-            ..
-        } in library.0.values_mut()
+        let mut library = parse_machine_parts(&bytes).map_err(|e| {
+            let source = String::from_utf8_lossy(&bytes);
+            ContentIssue::from_parts_error(&load_context.path().to_string_lossy(), &source, &e)
+        })?;
+        // paths are relative to the file, content packs keep their images next to it
+        for config in library.0.values_mut() {
+            let paths = config
+                .subassemblies
+                .iter_mut()
+                .flat_map(SubAssembly::paths_mut);
+            for path in std::iter::once(&mut config.icon.path).chain(paths) {
+                if !path.is_empty() {
+                    *path = load_context.asset_path().resolve_embed(path)?.to_string();
+                }
+            }
+        }
+        let collider_cache = match load_context.read_asset_bytes(COLLIDER_CACHE_PATH).await {
+            Ok(bytes) => ron::de::from_bytes::<ColliderCache>(&bytes).unwrap_or_else(|e| {
+                warn!("ignoring {COLLIDER_CACHE_PATH}: {e}");
+                ColliderCache::default()
+            }),
+            Err(_) => ColliderCache::default(),
+        };
+        for (
+            name,
+            MachinePartConfig {
+                subassemblies,
+                icon,
+                texture_info,
+                ..
+            },
+        ) in library.0.iter_mut()
         {
             //load icon:
             if !icon.path.is_empty() {
@@ -81,21 +285,25 @@ impl AssetLoader for MachinePartConfigByTypeLoader {
                 match subassembly {
                     SubAssembly::FluidFilter {
                         mesh_image_path,
+                        collider_settings,
                         colliders,
                         ..
                     }
                     | SubAssembly::ConveyorBelt {
                         mesh_image_path,
+                        collider_settings,
                         colliders,
                         ..
                     }
                     | SubAssembly::FluidFilterButton {
                         mesh_image_path,
+                        collider_settings,
                         colliders,
                         ..
                     }
                     | SubAssembly::Collider {
                         mesh_image_path,
+                        collider_settings,
                         colliders,
                         ..
                     } => {
@@ -105,25 +313,15 @@ impl AssetLoader for MachinePartConfigByTypeLoader {
                             .with_static_type()
                             .load::<Image>(mesh_image_path.clone())
                             .await?;
-                        let image = loaded_image.get();
-                        let size = image.size();
-                        let rotations = texture_info.rotations;
-
-                        let row_height = size.y / rotations;
-                        let mut rotation_colliders = Vec::new();
-
-                        for rot in 0..rotations {
-                            let offset = UVec2::new(0, row_height * rot);
-                            let region_size = UVec2::new(size.x, row_height);
-                            let new_colliders =
-                                colliders_from_image_region(image, offset, region_size);
-                            rotation_colliders.push(new_colliders);
-                        }
-
-                        *colliders = rotation_colliders;
+                        *colliders = collider_cache.rotation_colliders(
+                            loaded_image.get(),
+                            texture_info.rotations,
+                            collider_settings,
+                        );
                     }
                     SubAssembly::TeaSensor {
                         mesh_image_path,
+                        collider_settings,
                         colliders,
                         icon,
                         icon_asset_path,
@@ -135,24 +333,31 @@ impl AssetLoader for MachinePartConfigByTypeLoader {
                             .with_static_type()
                             .load::<Image>(mesh_image_path.clone())
                             .await?;
-                        let image = loaded_image.get();
-                        let size = image.size();
-                        let rotations = texture_info.rotations;
-
-                        let row_height = size.y / rotations;
-                        let mut rotation_colliders = Vec::new();
-
-                        for rot in 0..rotations {
-                            let offset = UVec2::new(0, row_height * rot);
-                            let region_size = UVec2::new(size.x, row_height);
-                            let new_colliders =
-                                colliders_from_image_region(image, offset, region_size);
-                            rotation_colliders.push(new_colliders);
+                        *colliders = collider_cache.rotation_colliders(
+                            loaded_image.get(),
+                            texture_info.rotations,
+                            collider_settings,
+                        );
+                        for (rotation, row) in colliders.iter().enumerate() {
+                            if row.len() != 1 {
+                                let source = String::from_utf8_lossy(&bytes);
+                                let issue = ContentIssue::sensor_shapes(
+                                    &source,
+                                    name,
+                                    mesh_image_path,
+                                    rotation,
+                                    row.len(),
+                                );
+                                // a cup counting particles with a shape nobody meant is worse
+                                // than a part that fails to load
+                                return Err(issue.into());
+                            }
                         }
 
-                        *colliders = rotation_colliders;
-
-                        *icon = load_context.loader().with_static_type().load::<Image>(icon_asset_path.clone());
+                        *icon = load_context
+                            .loader()
+                            .with_static_type()
+                            .load::<Image>(icon_asset_path.clone());
                     }
                     SubAssembly::Sprite {
                         sprite,
@@ -231,6 +436,22 @@ impl AssetLoader for MachinePartConfigByTypeLoader {
                         *particle_image = load_context
                             .add_loaded_labeled_asset(particle_texture_path.clone(), loaded_image);
                     }
+                    SubAssembly::Sound {
+                        ambient_path,
+                        ambient,
+                        one_shot_path,
+                        one_shot,
+                        ..
+                    } => {
+                        for (path, handle) in [(ambient_path, ambient), (one_shot_path, one_shot)] {
+                            *handle = path.as_ref().map(|path| {
+                                load_context
+                                    .loader()
+                                    .with_static_type()
+                                    .load::<Sample>(path.clone())
+                            });
+                        }
+                    }
                     SubAssembly::FlowField {
                         flow_texture_path,
                         flow_texture,
@@ -280,111 +501,44 @@ impl AssetLoader for MachinePartConfigByTypeLoader {
     }
 }
 
-fn colliders_from_image_region(mesh_image: &Image, offset: UVec2, size: UVec2) -> Vec<Compound> {
-    let mut pixels = vec![0.0; (size.x * size.y) as usize];
-    for x in 0..size.x {
-        for y in 0..size.y {
-            let img_x = offset.x + x;
-            let img_y = offset.y + y;
-            if let Ok(color) = mesh_image.get_color_at(img_x, img_y) {
-                if color.is_fully_opaque() {
-                    let i = y * size.x + x;
-                    pixels[i as usize] = 1.0;
-                }
-            }
-        }
-    }
-    let c = contour::ContourBuilder::new(size.x as usize, size.y as usize, false);
-    let contour = c.contours(&pixels, &[0.5]).unwrap();
-    let polygons: MultiPolygon<f64> = contour[0].clone().into_inner().0;
-    let polys = divide_reduce::<MultiPolygon<f64>>(vec![polygons], |a, b| a.union(&b))
-        .unwrap_or(MultiPolygon::new(vec![]));
-
-    let polys: Vec<_> = polys
-        .into_iter()
-        .map(|p| {
-            geo::Polygon::new(
-                removed_in_line(p.exterior()),
-                p.interiors().iter().map(removed_in_line).collect(),
-            )
-        })
-        .collect();
-
-    polys
-        .into_iter()
-        .map(|poly| {
-            let exterior: Vec<Vec2> = poly
-                .exterior()
-                .into_iter()
-                .map(|p| {
-                    Vec2::new(
-                        p.x as f32 - size.x as f32 / 2.0,
-                        -p.y as f32 + size.y as f32 / 2.0,
-                    )
-                })
-                .collect();
-            let indices = (0..exterior.len() as u32).collect_vec();
-            let collider = Collider::convex_decomposition_with_config(
-                exterior,
-                indices.windows(2).map(|i| [i[0], i[1]]).collect_vec(),
-                &VhacdParameters {
-                    ..Default::default()
-                },
-            );
-            let shape = collider.shape().as_compound().unwrap();
-            shape.clone()
-        })
-        .collect()
-}
-
-fn _colliders_from_image(mesh_image: &Image) -> Vec<Compound> {
-    let size = UVec2::new(mesh_image.width(), mesh_image.height());
-    colliders_from_image_region(mesh_image, UVec2::ZERO, size)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn divide_reduce<T>(list: Vec<T>, mut reduction: impl FnMut(T, T) -> T) -> Option<T> {
-    let mut queue = VecDeque::from(list);
+    const PART: &str = "cost: 1, is_dynamic: false";
 
-    while queue.len() > 1 {
-        for _ in 0..(queue.len() / 2) {
-            let (one, two) = (queue.pop_front().unwrap(), queue.pop_front().unwrap());
-            queue.push_back(reduction(one, two));
-        }
+    #[test]
+    fn parts_defined_twice_are_an_error() {
+        let ron = format!(r#"({{ "Shelf": ({PART}), "Fan": ({PART}), "Shelf": ({PART}) }})"#);
+        let error = parse_machine_parts(ron.as_bytes()).unwrap_err();
+        assert!(
+            matches!(&error, MachinePartsParseError::DuplicatePart(name) if name == "Shelf"),
+            "{error}"
+        );
     }
 
-    queue.pop_back()
-}
-fn removed_in_line(line: &geo::LineString<f64>) -> geo::LineString<f64> {
-    if line.coords_count() < 3 {
-        return line.clone();
+    #[test]
+    fn cycles_among_templates_are_an_error() {
+        let ron = format!(
+            r#"({{
+                "A": (template: true, extends: "B"),
+                "B": (template: true, extends: "A"),
+                "Shelf": ({PART}),
+            }})"#
+        );
+        let error = parse_machine_parts(ron.as_bytes()).unwrap_err();
+        assert!(
+            matches!(error, MachinePartsParseError::InheritanceCycle(_)),
+            "{error}"
+        );
     }
 
-    let mut points = line.coords().copied();
-    let (mut a, mut b) = points.next_tuple().unwrap();
-    let mut kept = vec![];
-
-    fn along_path(a: Coord<f64>, b: Coord<f64>, c: Coord<f64>) -> bool {
-        let dir_1 = (c - b).try_normalize().unwrap_or_default();
-        let dir_2 = (b - a).try_normalize().unwrap_or_default();
-        dir_1.dot_product(dir_2) >= 0.9995
-    }
-    // maybe add first one
-    {
-        let mut coords = line.coords().copied();
-        let b = coords.next().unwrap();
-        let c = coords.next().unwrap();
-        let _ = coords.next_back().unwrap(); // last coord is a duplicate
-        let a = coords.next_back().unwrap();
-        if !along_path(a, b, c) {
-            kept.push(b);
-        }
-    }
-
-    for p in points {
-        if !along_path(a, b, p) {
-            kept.push(b);
-        }
-        (a, b) = (b, p);
+    #[test]
+    fn templates_are_inherited_but_left_out() {
+        let ron =
+            format!(r#"({{ "Base": (template: true, {PART}), "Shelf": (extends: "Base") }})"#);
+        let library = parse_machine_parts(ron.as_bytes()).unwrap();
+        assert_eq!(library.0.keys().collect::<Vec<_>>(), ["Shelf"]);
+        assert_eq!(library.0["Shelf"].cost, 1);
     }
-    LineString::<f64>::new(kept)
 }
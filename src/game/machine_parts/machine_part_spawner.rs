@@ -1,31 +1,48 @@
 use crate::prelude::*;
 use crate::read_single_field_variant;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub struct MachinePartSpawnerPlugin;
 
 #[derive(Component)]
 pub struct IsInitialPart;
 
+/// Numbers the parts in the order they were spawned since the level was loaded, so the same
+/// part can be found again in a replay.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartId(pub u32);
+
+/// The [`PartId`] given to the next spawned part.
+#[derive(Resource, Default)]
+pub struct NextPartId(u32);
+
 impl Plugin for MachinePartSpawnerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<NextPartId>().add_systems(
             Update,
-            listen_to_spawn_requests
-                .run_if(resource_exists::<MachinePartConfigByType>)
-                .run_if(resource_exists::<AudioSources>),
+            (
+                reset_part_ids.run_if(resource_exists_and_changed::<LoadedLevel>),
+                listen_to_spawn_requests,
+                listen_to_erase_requests,
+            )
+                .chain()
+                .run_if(resource_exists::<MachinePartConfigByType>),
         );
     }
 }
 
-fn listen_to_spawn_requests(
+fn reset_part_ids(mut next_id: ResMut<NextPartId>) {
+    next_id.0 = 0;
+}
+
+pub fn listen_to_spawn_requests(
     mut request_listener: EventReader<MachinePartRequest>,
     machine_part_config_by_type: Res<MachinePartConfigByType>,
+    mut next_id: ResMut<NextPartId>,
     mut available_zen_points: ResMut<AvailableZenPoints>,
     mut commands: Commands,
     editor_mode: Res<EditorMode>,
-    sounds: Res<AudioSources>,
-    settings: Res<Settings>,
     #[cfg(debug_assertions)] mut meshes: ResMut<Assets<Mesh>>,
     #[cfg(debug_assertions)] mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
@@ -46,26 +63,51 @@ fn listen_to_spawn_requests(
                 info!("Approved spawn request {:?}", spawn_request);
 
                 #[cfg(not(debug_assertions))]
-                let spawned = part_config.spawn(
-                    spawn_request.part_type.clone(),
-                    &mut commands,
-                    &sounds,
-                    &settings,
-                );
+                let spawned = part_config.spawn(spawn_request.part_type.clone(), &mut commands);
                 #[cfg(debug_assertions)]
                 let spawned = part_config.spawn(
                     spawn_request.part_type.clone(),
                     &mut commands,
-                    &sounds,
-                    &settings,
                     &mut meshes,
                     &mut materials,
                 );
 
+                commands.entity(spawned).insert(PartId(next_id.0));
+                next_id.0 += 1;
                 if spawn_request.initial_part {
                     commands.entity(spawned).insert(IsInitialPart);
                 }
             }
+        } else {
+            warn!(
+                "Unknown machine part \"{}\" requested, it is not in machine_parts.ron",
+                spawn_request.part_type.name
+            );
+        }
+    }
+}
+
+pub fn listen_to_erase_requests(
+    mut request_listener: EventReader<MachinePartRequest>,
+    machine_part_config_by_type: Res<MachinePartConfigByType>,
+    mut available_zen_points: ResMut<AvailableZenPoints>,
+    mut commands: Commands,
+    editor_mode: Res<EditorMode>,
+    parts: Query<(&MachinePartType, Has<IsInitialPart>), With<SpawnedMachinePart>>,
+) {
+    for entity in read_single_field_variant!(request_listener, MachinePartRequest::EraseMachinePart)
+    {
+        let erasable = parts
+            .get(*entity)
+            .ok()
+            .filter(|(_, is_initial)| editor_mode.0 || !is_initial);
+        let Some((part_type, _)) = erasable else {
+            warn!("{entity} is not an erasable part");
+            continue;
+        };
+        if let Some(part_config) = machine_part_config_by_type.0.get(&part_type.name) {
+            available_zen_points.refund(part_config.cost);
         }
+        commands.entity(*entity).despawn();
     }
 }
@@ -5,6 +5,7 @@ use crate::{
 use bevy::prelude::*;
 
 pub mod animator;
+pub mod collider_cache;
 pub mod consts;
 pub mod events;
 pub mod flow_field;
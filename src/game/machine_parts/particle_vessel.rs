@@ -7,6 +7,7 @@ use crate::{
     prelude::{Config, Particle, ParticleContents},
     screens::Screen,
     ui::WATER,
+    util::image_geometry::{RgbaBuffer, scan_image_for_circles},
 };
 
 pub struct ParticleVesselPlugin;
@@ -48,7 +49,8 @@ pub fn do_particle_vessels(
             continue;
         };
 
-        let relative_positions = scan_image_for_circles(img, vessel.particle_radius as i32);
+        let image = RgbaBuffer::from_image(img);
+        let relative_positions = scan_image_for_circles(&image, vessel.particle_radius as i32);
         // let relative_positions = vec![IVec2::new(0.0,m, y)]
 
         let mesh = meshes.add(Circle::new(vessel.particle_radius));
@@ -90,85 +92,3 @@ pub fn do_particle_vessels(
         }
     }
 }
-
-pub fn scan_image_for_circles(image: &Image, radius: i32) -> Vec<IVec2> {
-    let (width, height) = image.size().as_ivec2().into();
-    let mut valid_spawns = Vec::new();
-
-    let r_squared = radius * radius;
-
-    let mut pixel_data: Vec<bool> = Vec::new();
-    for y in 0..height {
-        for x in 0..width {
-            let color = image.get_color_at(x as u32, y as u32);
-            let is_transparent = color.is_ok_and(|c| c.is_fully_transparent());
-            pixel_data.push(!is_transparent);
-        }
-    }
-
-    // println!("Pixel data:");
-    // for y in 0..height {
-    //     for x in 0..width {
-    //         let idx = (y * width + x) as usize;
-    //         if pixel_data[idx] {
-    //             print!("██");
-    //         } else {
-    //             print!("  ");
-    //         }
-    //     }
-    //     println!();
-    // }
-
-    //check pixels are all true
-    for y in radius..height {
-        for x in radius..width {
-            let mut all_valid = true;
-
-            let mut valid_positions = Vec::new();
-
-            'outer: for dx in (0 - radius)..radius {
-                for dy in (0 - radius)..radius {
-                    let dist_sq = dx * dx + dy * dy;
-                    if dist_sq > r_squared {
-                        continue;
-                    }
-                    let nx = x + dx;
-                    let ny = y + dy;
-                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
-                        all_valid = false;
-                        break 'outer;
-                    }
-                    let idx = (ny * width + nx) as usize;
-                    if !pixel_data[idx] {
-                        all_valid = false;
-                        break 'outer;
-                    }
-                    valid_positions.push(IVec2::new(nx, ny));
-                }
-            }
-            if all_valid {
-                for pos in &valid_positions {
-                    let idx = (pos.y * width + pos.x) as usize;
-                    pixel_data[idx] = false;
-                }
-                // Subtract half the width and height to center the spawn positions
-                valid_spawns.push(IVec2::new(x - width / 2, height / 2 - y));
-            }
-        }
-    }
-
-    // println!("Pixel data:");
-    // for y in 0..height {
-    //     for x in 0..width {
-    //         let idx = (y * width + x) as usize;
-    //         if pixel_data[idx] {
-    //             print!("██");
-    //         } else {
-    //             print!("  ");
-    //         }
-    //     }
-    //     println!();
-    // }
-
-    valid_spawns
-}
@@ -31,7 +31,8 @@ fn flip_preview(
     if let PickingState::Placing(ref mut part_type) = *picking_state {
         let context = &mut part_type.context;
         if let Some(config) = machine_part_config_by_type.0.get(&part_type.name) {
-            if !config.texture_info.flippable {
+            // free rotation parts have a single row, so there is no mirrored half to switch to
+            if !config.texture_info.flippable || config.texture_info.free_rotation {
                 return;
             }
 
@@ -63,6 +64,13 @@ fn rotate_preview(
     if let PickingState::Placing(ref mut part_type) = *picking_state {
         let context = &mut part_type.context;
         if let Some(config) = machine_part_config_by_type.0.get(&part_type.name) {
+            if config.texture_info.free_rotation {
+                let step = on_rotate.0 as f32 * config.texture_info.rotation_snap_deg;
+                // positive steps turn clockwise, same as stepping through the sprite rows
+                context.angle_deg = config.texture_info.snapped_angle(context.angle_deg - step);
+                return;
+            }
+
             let mut max = config.texture_info.rotations as i32;
             if config.texture_info.flippable {
                 max /= 2;
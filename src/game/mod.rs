@@ -5,12 +5,18 @@ use bevy::prelude::*;
 pub mod camera;
 pub mod conveyor_belts;
 pub mod currency;
+pub mod environment;
+pub mod gamepad_cursor;
 pub mod heat;
+#[cfg(feature = "dev_native")]
+pub mod hot_reload;
 pub mod input_dispatch;
+pub mod inspector;
 pub mod levels;
 pub mod machine_parts;
 pub mod particles;
 pub mod physics;
+pub mod replay;
 pub mod settings;
 pub mod sound;
 pub mod tea;
@@ -23,6 +29,7 @@ pub fn plugin(app: &mut App) {
         settings::plugin,
         physics::plugin,
         camera::plugin,
+        environment::plugin,
         sound::plugin,
         input_dispatch::plugin,
         particles::plugin,
@@ -33,8 +40,17 @@ pub fn plugin(app: &mut App) {
         conveyor_belts::plugin,
         tea_particles::plugin,
         tutorial::plugin,
+        replay::plugin,
     ))
-    .add_plugins((MachinePartsPlugin, CurrencyPlugin));
+    .add_plugins((
+        MachinePartsPlugin,
+        CurrencyPlugin,
+        inspector::plugin,
+        gamepad_cursor::plugin,
+    ));
+
+    #[cfg(feature = "dev_native")]
+    app.add_plugins(hot_reload::plugin);
 }
 
 #[derive(PhysicsLayer, Default)]
@@ -1,4 +1,7 @@
-use crate::{game::ParticleLayer, prelude::*};
+use crate::{
+    game::{ParticleLayer, environment::Environment, inspector::ParticleView, replay::GameRng},
+    prelude::*,
+};
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use rand::prelude::*;
@@ -11,19 +14,20 @@ pub fn plugin(app: &mut App) {
         (
             despawn_particles,
             // trigger_fluid_filter_buttons,
-            (spawn_particles)
-                .before(crate::game::levels::prepare_levels),
         )
             .run_if(in_state(Screen::Gameplay)),
     );
+    // emitters follow physics time so they pause, step and scale with it
     app.add_systems(
-        PostUpdate,
-        (
-            (recolor_particles, mix_particles)
-                .before(crate::game::levels::prepare_levels),
-        )
+        FixedPostUpdate,
+        spawn_particles
+            .after(PhysicsSet::StepSimulation)
             .run_if(in_state(Screen::Gameplay)),
     );
+    app.add_systems(
+        PostUpdate,
+        (recolor_particles, mix_particles).run_if(in_state(Screen::Gameplay)),
+    );
     // .add_observer(activate_fluid_filter)
     // .add_observer(deactivate_fluid_filter)
 }
@@ -38,17 +42,24 @@ pub struct Particle {
     pub contents: ParticleContents,
 }
 
+/// Tea above which a particle counts as brewed.
+pub const TEA_THRESHOLD: f32 = 0.5;
+/// Milk above which a particle counts as milky.
+pub const MILK_THRESHOLD: f32 = 0.15;
+/// Sugar above which a particle counts as sweet.
+pub const SUGAR_THRESHOLD: f32 = 0.5;
+
 impl ParticleContents {
     pub fn is_tea(&self) -> bool {
-        self.tea > 0.5
+        self.tea > TEA_THRESHOLD
     }
 
     pub fn is_milky(&self) -> bool {
-        self.milk > 0.15
+        self.milk > MILK_THRESHOLD
     }
 
     pub fn is_sweet(&self) -> bool {
-        self.sugar > 0.5
+        self.sugar > SUGAR_THRESHOLD
     }
 
     pub fn clamp(&mut self) {
@@ -59,7 +70,7 @@ impl ParticleContents {
     }
 }
 
-#[derive(Default, Debug, Copy, Clone, Reflect, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Reflect, Serialize, Deserialize)]
 pub struct ParticleContents {
     #[serde(default = "default_heat")]
     pub heat: f32,
@@ -148,7 +159,7 @@ impl ParticleEmitter {
 }
 
 fn spawn_particles(
-    time: Res<Time>,
+    time: Res<Time<Physics>>,
     cfg: Res<Config>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -156,13 +167,15 @@ fn spawn_particles(
     mut emitter: Query<(&mut ParticleEmitter, &GlobalTransform)>,
     droplet_count_query: Query<&Particle>,
     editor_mode: Res<EditorMode>,
+    mut rng: ResMut<GameRng>,
+    environment: Res<Environment>,
 ) {
     if editor_mode.0 {
         return;
     }
-    let mut rng = rand::thread_rng();
+    let rng = &mut rng.rng;
 
-    let max_particles = cfg.physics.water.max_particles as usize;
+    let max_particles = environment.max_particles;
     let current_droplet_count: usize = droplet_count_query.iter().count();
 
     for (mut emitter, global_transform) in emitter.iter_mut() {
@@ -256,13 +269,16 @@ fn despawn_particles(
 
 fn recolor_particles(
     mut commands: Commands,
-    particles: Query<(Entity, &Particle), Changed<Particle>>,
+    view: Res<ParticleView>,
+    particles: Query<(Entity, Ref<Particle>)>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     for (entity, particle) in &particles {
-        let color = WATER
-            .mix(&BREWED_TEA, particle.contents.tea.min(1.0))
-            .lighter(particle.contents.milk.min(1.0));
+        // switching the view repaints everything, otherwise only what changed
+        if !view.is_changed() && !particle.is_changed() {
+            continue;
+        }
+        let color = view.color(&particle.contents);
 
         if commands.get_entity(entity).is_err() {
             continue; // Entity no longer exists
@@ -9,8 +9,46 @@ pub fn plugin(app: &mut App) {
     app.add_systems(OnEnter(PhysicsState::Paused), pause);
     app.add_systems(OnEnter(Screen::Gameplay), start_paused);
     app.add_systems(OnEnter(PhysicsState::Running), run);
+
+    app.init_resource::<SimulationSpeed>()
+        .init_resource::<PendingStep>()
+        .add_systems(OnExit(Screen::Gameplay), reset_speed)
+        .add_systems(
+            Update,
+            apply_simulation_speed.run_if(resource_changed::<SimulationSpeed>),
+        )
+        .add_systems(FixedLast, finish_step)
+        .add_observer(change_speed)
+        .add_observer(step_physics);
+}
+
+/// Time scales selectable with [`Action::SlowDown`] and [`Action::SpeedUp`].
+pub const SIMULATION_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Index into [`SIMULATION_SPEEDS`] applied to [`Time<Physics>`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSpeed(pub usize);
+
+impl Default for SimulationSpeed {
+    fn default() -> Self {
+        Self(2)
+    }
 }
 
+impl SimulationSpeed {
+    pub fn factor(&self) -> f32 {
+        SIMULATION_SPEEDS[self.0]
+    }
+
+    pub fn label(&self, localization: &Localization) -> String {
+        localization.format("{speed}x", &[("speed", &self.factor().to_string())])
+    }
+}
+
+/// Set while a single physics tick requested with [`OnPhysicsStep`] is in flight.
+#[derive(Resource, Default)]
+struct PendingStep(bool);
+
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum PhysicsState {
     Paused,
@@ -31,3 +69,39 @@ fn run(mut time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>)
     //time.unpause();
     physics_time.unpause();
 }
+
+fn reset_speed(mut speed: ResMut<SimulationSpeed>) {
+    *speed = SimulationSpeed::default();
+}
+
+fn apply_simulation_speed(speed: Res<SimulationSpeed>, mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.set_relative_speed(speed.factor());
+}
+
+fn change_speed(trigger: Trigger<OnSimulationSpeedChange>, mut speed: ResMut<SimulationSpeed>) {
+    let index = speed.0 as i32 + trigger.event().0;
+    let index = index.clamp(0, SIMULATION_SPEEDS.len() as i32 - 1) as usize;
+    if index != speed.0 {
+        speed.0 = index;
+    }
+}
+
+/// Lets one fixed tick of physics through while paused, [`finish_step`] pauses it again.
+fn step_physics(
+    _: Trigger<OnPhysicsStep>,
+    state: Res<State<PhysicsState>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut pending: ResMut<PendingStep>,
+) {
+    if *state.get() == PhysicsState::Paused {
+        physics_time.unpause();
+        pending.0 = true;
+    }
+}
+
+fn finish_step(mut physics_time: ResMut<Time<Physics>>, mut pending: ResMut<PendingStep>) {
+    if pending.0 {
+        physics_time.pause();
+        pending.0 = false;
+    }
+}
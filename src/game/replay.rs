@@ -0,0 +1,376 @@
+//! Records what the player does during a level and plays it back.
+//!
+//! Placements and erasures are recorded from [`MachinePartRequest`]s, the rest from the
+//! triggers sent by `input_dispatch`. Playback sends the same requests and triggers back, so
+//! a replay goes through exactly the code paths the player used.
+//!
+//! Entries are timed in fixed ticks, and playback runs exactly one fixed tick per frame, so
+//! every input lands on the same physics step it was recorded on.
+
+use crate::{cli::Args, prelude::*};
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<GameRng>()
+        .init_resource::<ReplayRecorder>()
+        .add_systems(OnEnter(Screen::Title), start_pending_replay)
+        .add_systems(OnExit(Screen::Gameplay), save_recording)
+        .add_systems(FixedFirst, count_ticks)
+        .add_systems(
+            Update,
+            (
+                begin_session.run_if(resource_exists_and_changed::<LoadedLevel>),
+                (record_part_requests, play_back)
+                    .chain()
+                    .run_if(in_state(Screen::Gameplay)),
+            )
+                .chain()
+                // erasures are recorded by part id, which is gone once the part is despawned
+                .before(listen_to_spawn_requests)
+                .before(listen_to_erase_requests),
+        )
+        .add_observer(record_rotate)
+        .add_observer(record_flip)
+        .add_observer(record_physics_toggle)
+        .add_observer(record_speed_change)
+        .add_observer(record_physics_step);
+}
+
+/// Where the last session's recording is written on native builds.
+pub const LATEST_REPLAY_PATH: &str = "replays/latest.replay.ron";
+
+/// The only source of randomness for the simulation, reseeded for every level so a replay
+/// spawns the same particles.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+    /// Seeded from the same `seed` for sound variations, kept apart from `rng` because how
+    /// many sounds a frame plays depends on the frame rate.
+    pub audio: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(rand::random())
+    }
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            audio: StdRng::seed_from_u64(!seed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    /// [`LevelConfig::name`] of the recorded level.
+    pub level: String,
+    pub seed: u64,
+    pub entries: Vec<ReplayEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    /// Fixed ticks since the level was loaded.
+    pub tick: u64,
+    pub action: ReplayAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReplayAction {
+    Place(MachinePartType),
+    Erase(PartId),
+    Rotate(i32),
+    Flip,
+    PhysicsToggle,
+    SimulationSpeed(i32),
+    PhysicsStep,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        ron::de::from_bytes(&bytes).map_err(|e| format!("{}:{e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// A replay given on the command line, started once the title screen is reached.
+#[derive(Resource)]
+pub struct PendingReplay(pub Replay);
+
+#[derive(Resource)]
+struct ReplayPlayback {
+    replay: Replay,
+    next: usize,
+    tick: u64,
+}
+
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    replay: Replay,
+    tick: u64,
+}
+
+impl ReplayRecorder {
+    fn push(&mut self, action: ReplayAction) {
+        let tick = self.tick;
+        self.replay.entries.push(ReplayEntry { tick, action });
+    }
+}
+
+/// Locks the frame time to the fixed timestep, so each frame of a playback runs one tick.
+fn lock_to_fixed_ticks(commands: &mut Commands, fixed_time: &Time<Fixed>) {
+    commands.insert_resource(TimeUpdateStrategy::ManualDuration(fixed_time.timestep()));
+}
+
+fn stop_playback(commands: &mut Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+    commands.insert_resource(TimeUpdateStrategy::Automatic);
+}
+
+fn count_ticks(mut recorder: ResMut<ReplayRecorder>, playback: Option<ResMut<ReplayPlayback>>) {
+    recorder.tick += 1;
+    if let Some(mut playback) = playback {
+        playback.tick += 1;
+    }
+}
+
+fn start_pending_replay(
+    mut commands: Commands,
+    pending: Option<Res<PendingReplay>>,
+    level_list: Res<LevelList>,
+    level_configs: Res<Assets<LevelConfig>>,
+    fixed_time: Res<Time<Fixed>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(pending) = pending else {
+        return;
+    };
+    let replay = pending.0.clone();
+    commands.remove_resource::<PendingReplay>();
+
+    let level = level_list.0.iter().find(|handle| {
+        level_configs
+            .get(*handle)
+            .is_some_and(|level| level.name == replay.level)
+    });
+    let Some(level) = level else {
+        error!("replay level \"{}\" not found", replay.level);
+        return;
+    };
+
+    info!(
+        "playing back {} inputs on \"{}\"",
+        replay.entries.len(),
+        replay.level
+    );
+    commands.insert_resource(LoadedLevel(level.clone()));
+    commands.insert_resource(ReplayPlayback {
+        replay,
+        next: 0,
+        tick: 0,
+    });
+    lock_to_fixed_ticks(&mut commands, &fixed_time);
+    next_screen.set(Screen::Gameplay);
+}
+
+/// Starts a new recording, and restarts the playback clock, whenever a level is loaded.
+fn begin_session(
+    mut commands: Commands,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut rng: ResMut<GameRng>,
+    playback: Option<ResMut<ReplayPlayback>>,
+    args: Option<Res<Args>>,
+    loaded_level: Res<LoadedLevel>,
+    level_configs: Res<Assets<LevelConfig>>,
+) {
+    save(&recorder.replay);
+
+    let level = level_configs
+        .get(&loaded_level.0)
+        .map(|level| level.name.clone())
+        .unwrap_or_default();
+
+    // `--seed` stands in for the random seed, a replay still brings its own
+    let seed = args.and_then(|args| args.seed);
+    let fresh = || seed.map_or_else(GameRng::default, GameRng::from_seed);
+    *rng = match playback {
+        Some(mut playback) if playback.replay.level == level => {
+            playback.next = 0;
+            playback.tick = 0;
+            GameRng::from_seed(playback.replay.seed)
+        }
+        Some(_) => {
+            // the player moved on to another level, the replay doesn't apply there
+            stop_playback(&mut commands);
+            fresh()
+        }
+        None => fresh(),
+    };
+
+    *recorder = ReplayRecorder {
+        replay: Replay {
+            level,
+            seed: rng.seed,
+            entries: vec![],
+        },
+        tick: 0,
+    };
+}
+
+fn save_recording(
+    mut commands: Commands,
+    recorder: Res<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    save(&recorder.replay);
+    // a finished playback shouldn't carry over into the next level the player picks
+    if playback.is_some() {
+        stop_playback(&mut commands);
+    }
+}
+
+fn save(replay: &Replay) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if !replay.entries.is_empty() {
+        let path = Path::new(LATEST_REPLAY_PATH);
+        match replay.save(path) {
+            Ok(()) => info!("replay saved to {}", path.display()),
+            Err(e) => warn!("could not save replay: {e}"),
+        }
+    }
+}
+
+fn record_part_requests(
+    mut recorder: ResMut<ReplayRecorder>,
+    mut requests: EventReader<MachinePartRequest>,
+    playback: Option<Res<ReplayPlayback>>,
+    part_ids: Query<&PartId>,
+) {
+    for request in requests.read() {
+        if playback.is_some() {
+            continue;
+        }
+        match request {
+            // initial and free parts come from the level itself, not the player
+            MachinePartRequest::SpawnMachinePart(spawn) if !spawn.initial_part && !spawn.free => {
+                recorder.push(ReplayAction::Place(spawn.part_type.clone()));
+            }
+            MachinePartRequest::EraseMachinePart(entity) => {
+                if let Ok(id) = part_ids.get(*entity) {
+                    recorder.push(ReplayAction::Erase(*id));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn record_rotate(
+    trigger: Trigger<OnRotate>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_none() {
+        recorder.push(ReplayAction::Rotate(trigger.event().0));
+    }
+}
+
+fn record_flip(
+    _: Trigger<OnFlip>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_none() {
+        recorder.push(ReplayAction::Flip);
+    }
+}
+
+fn record_physics_toggle(
+    _: Trigger<OnPhysicsToggle>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_none() {
+        recorder.push(ReplayAction::PhysicsToggle);
+    }
+}
+
+fn record_speed_change(
+    trigger: Trigger<OnSimulationSpeedChange>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_none() {
+        recorder.push(ReplayAction::SimulationSpeed(trigger.event().0));
+    }
+}
+
+fn record_physics_step(
+    _: Trigger<OnPhysicsStep>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_none() {
+        recorder.push(ReplayAction::PhysicsStep);
+    }
+}
+
+fn play_back(
+    mut commands: Commands,
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut requests: EventWriter<MachinePartRequest>,
+    parts: Query<(Entity, &PartId)>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+
+    while let Some(entry) = playback.replay.entries.get(playback.next) {
+        if entry.tick > playback.tick {
+            break;
+        }
+        match entry.action.clone() {
+            ReplayAction::Place(part_type) => {
+                requests.write(MachinePartRequest::SpawnMachinePart(
+                    MachinePartSpawnRequest {
+                        location: part_type.context.position,
+                        part_type,
+                        ..default()
+                    },
+                ));
+            }
+            ReplayAction::Erase(id) => match parts.iter().find(|(_, part_id)| **part_id == id) {
+                Some((entity, _)) => {
+                    requests.write(MachinePartRequest::EraseMachinePart(entity));
+                }
+                None => warn!("replay erases {id:?}, which was never spawned"),
+            },
+            ReplayAction::Rotate(steps) => commands.trigger(OnRotate(steps)),
+            ReplayAction::Flip => commands.trigger(OnFlip),
+            ReplayAction::PhysicsToggle => commands.trigger(OnPhysicsToggle),
+            ReplayAction::SimulationSpeed(steps) => {
+                commands.trigger(OnSimulationSpeedChange(steps))
+            }
+            ReplayAction::PhysicsStep => commands.trigger(OnPhysicsStep),
+        }
+        playback.next += 1;
+    }
+}
@@ -1,17 +1,25 @@
-use crate::prelude::*;
+use crate::{localization::DEFAULT_LANGUAGE, prelude::*, util::user_dir};
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
 
 pub fn plugin(app: &mut App) {
-    app.init_resource::<Settings>();
-    app.add_plugins(InputManagerPlugin::<Action>::default())
-        .add_systems(Startup, spawn_player_input_map)
+    app.init_resource::<Settings>()
+        .add_plugins(InputManagerPlugin::<Action>::default())
+        .add_systems(Startup, (load_settings, spawn_player_input_map).chain())
         .add_systems(
-            OnEnter(Screen::Title),
-            inject_settings_from_cfg.run_if(resource_exists::<Config>),
+            Update,
+            (
+                sound_from_cfg.run_if(resource_added::<Config>),
+                save_tutorial_progress.run_if(resource_changed::<Settings>),
+            ),
         );
 }
 
+/// Name of the saved settings inside [`user_dir::config_dir`].
+const SETTINGS_FILE: &str = "settings.ron";
+
 #[derive(Resource)]
 pub struct Settings {
     pub sound: Sound,
@@ -22,6 +30,9 @@ pub struct Settings {
     pub modals: Vec<Modal>,
     pub last_screen: Screen,
     pub tutorial: bool,
+    pub keybinds: Keybinds,
+    /// Code of a language in `locales.ron`.
+    pub language: String,
 }
 
 impl Settings {
@@ -31,6 +42,33 @@ impl Settings {
     pub fn sfx(&self) -> f32 {
         self.sound.general * self.sound.sfx
     }
+
+    /// Writes the volumes, the tutorial flag, the bindings and the language to the user
+    /// config directory.
+    pub fn save(&self) {
+        let Some(path) = settings_path() else {
+            debug!("no user config directory, settings are not saved");
+            return;
+        };
+        let saved = SavedSettings {
+            sound: self.sound.clone(),
+            tutorial: self.tutorial,
+            keybinds: self.keybinds.clone(),
+            language: self.language.clone(),
+        };
+        let result = ron::ser::to_string_pretty(&saved, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&path, text).map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => info!("settings saved to {}", path.display()),
+            Err(e) => warn!("could not save settings to {}: {e}", path.display()),
+        }
+    }
 }
 
 impl Default for Settings {
@@ -40,6 +78,8 @@ impl Default for Settings {
             sound: Sound::default(),
             modals: vec![],
             tutorial: true,
+            keybinds: Keybinds::default(),
+            language: DEFAULT_LANGUAGE.to_string(),
         }
     }
 }
@@ -52,19 +92,90 @@ pub enum Modal {
     Settings,
 }
 
-fn inject_settings_from_cfg(mut commands: Commands, cfg: Res<Config>) {
-    commands.insert_resource(Settings {
-        sound: cfg.sound.clone(),
-        ..Default::default()
-    });
+/// The part of [`Settings`] kept between runs.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct SavedSettings {
+    sound: Sound,
+    tutorial: bool,
+    keybinds: Keybinds,
+    language: String,
 }
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+impl Default for SavedSettings {
+    fn default() -> Self {
+        let settings = Settings::default();
+        Self {
+            sound: settings.sound,
+            tutorial: settings.tutorial,
+            keybinds: settings.keybinds,
+            language: settings.language,
+        }
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    user_dir::config_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
+
+fn read_saved_settings() -> Option<SavedSettings> {
+    let path = settings_path()?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    match ron::de::from_str::<SavedSettings>(&text) {
+        Ok(saved) => Some(saved),
+        Err(e) => {
+            warn!("ignoring {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn load_settings(mut settings: ResMut<Settings>) {
+    if let Some(saved) = read_saved_settings() {
+        settings.sound = saved.sound;
+        settings.tutorial = saved.tutorial;
+        settings.keybinds = saved.keybinds;
+        settings.language = saved.language;
+        // actions added since the file was written get their default keys
+        settings.keybinds.fill_missing();
+    }
+}
+
+/// Volumes come from `config.ron` until the player saves their own.
+fn sound_from_cfg(mut settings: ResMut<Settings>, cfg: Res<Config>) {
+    if read_saved_settings().is_none() {
+        settings.sound = cfg.sound.clone();
+    }
+}
+
+fn save_tutorial_progress(settings: Res<Settings>, mut last: Local<Option<bool>>) {
+    if last.is_some_and(|tutorial| tutorial != settings.tutorial) {
+        settings.save();
+    }
+    *last = Some(settings.tutorial);
+}
+
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Hash,
+    Debug,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
 pub enum Action {
     DebugNextLevel,
     Restart,
     TogglePause,
     ToggleUiDebug,
+    ToggleColliderOverlay,
+    CycleParticleView,
     Back,
 
     RotateCcw,
@@ -72,22 +183,215 @@ pub enum Action {
     Flip,
 
     TogglePhysics,
+    SlowDown,
+    SpeedUp,
+    StepPhysics,
+
+    #[actionlike(DualAxis)]
+    Pan,
+    #[actionlike(Axis)]
+    Zoom,
+
+    /// Moves the gamepad cursor.
+    #[actionlike(DualAxis)]
+    Cursor,
+    /// Clicks with the gamepad cursor.
+    Place,
+    /// Picks the next part to place, or the eraser after the last one.
+    CyclePart,
+}
+
+impl Action {
+    /// Actions bound to a single button, the ones the keybinding screen can change.
+    pub const REBINDABLE: [Action; 14] = [
+        Action::TogglePhysics,
+        Action::RotateCcw,
+        Action::RotateCw,
+        Action::Flip,
+        Action::SlowDown,
+        Action::SpeedUp,
+        Action::StepPhysics,
+        Action::Restart,
+        Action::TogglePause,
+        Action::CycleParticleView,
+        Action::Back,
+        Action::ToggleColliderOverlay,
+        Action::ToggleUiDebug,
+        Action::DebugNextLevel,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::DebugNextLevel => "Skip level (debug)",
+            Action::Restart => "Restart level",
+            Action::TogglePause => "Pause",
+            Action::ToggleUiDebug => "UI debug",
+            Action::ToggleColliderOverlay => "Collider overlay",
+            Action::CycleParticleView => "Particle view",
+            Action::Back => "Back",
+            Action::RotateCcw => "Rotate left",
+            Action::RotateCw => "Rotate right",
+            Action::Flip => "Flip",
+            Action::TogglePhysics => "Start/stop",
+            Action::SlowDown => "Slower",
+            Action::SpeedUp => "Faster",
+            Action::StepPhysics => "Step",
+            Action::Pan => "Pan",
+            Action::Zoom => "Zoom",
+            Action::Cursor => "Cursor",
+            Action::Place => "Place",
+            Action::CyclePart => "Next part",
+        }
+    }
 }
 
-fn spawn_player_input_map(mut commands: Commands) {
-    let mut input_map = InputMap::default();
+/// Keys that pan the camera. They aren't rebindable, so nothing else may use them.
+const PAN_KEYS: [KeyCode; 8] = [
+    KeyCode::KeyW,
+    KeyCode::KeyA,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowRight,
+];
+
+/// Keyboard and gamepad buttons of the actions that aren't axes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybinds {
+    pub keys: BTreeMap<Action, Vec<KeyCode>>,
+    pub gamepad: BTreeMap<Action, Vec<GamepadButton>>,
+}
 
-    //input_map.insert(Action::Restart, KeyCode::KeyR);
-    input_map.insert(Action::DebugNextLevel, KeyCode::KeyN);
+impl Default for Keybinds {
+    fn default() -> Self {
+        let mut keybinds = Self {
+            keys: Self::default_keys(),
+            gamepad: Self::default_gamepad(),
+        };
+        keybinds.fill_missing();
+        keybinds
+    }
+}
 
-    input_map.insert(Action::ToggleUiDebug, KeyCode::Backquote);
-    input_map.insert(Action::TogglePause, KeyCode::KeyP);
-    input_map.insert(Action::Back, KeyCode::Escape);
+impl Keybinds {
+    fn default_keys() -> BTreeMap<Action, Vec<KeyCode>> {
+        [
+            // (Action::Restart, KeyCode::KeyR),
+            (Action::DebugNextLevel, KeyCode::KeyN),
+            (Action::ToggleUiDebug, KeyCode::Backquote),
+            (Action::ToggleColliderOverlay, KeyCode::F2),
+            (Action::CycleParticleView, KeyCode::KeyV),
+            (Action::TogglePause, KeyCode::KeyP),
+            (Action::Back, KeyCode::Escape),
+            (Action::RotateCcw, KeyCode::KeyQ),
+            (Action::RotateCw, KeyCode::KeyE),
+            (Action::Flip, KeyCode::KeyF),
+            (Action::TogglePhysics, KeyCode::Space),
+            (Action::SlowDown, KeyCode::BracketLeft),
+            (Action::SpeedUp, KeyCode::BracketRight),
+            (Action::StepPhysics, KeyCode::Period),
+        ]
+        .into_iter()
+        .map(|(action, key)| (action, vec![key]))
+        .collect()
+    }
+
+    fn default_gamepad() -> BTreeMap<Action, Vec<GamepadButton>> {
+        [
+            (Action::Place, GamepadButton::South),
+            (Action::CyclePart, GamepadButton::West),
+            (Action::TogglePhysics, GamepadButton::North),
+            (Action::Back, GamepadButton::East),
+            (Action::RotateCcw, GamepadButton::LeftTrigger),
+            (Action::RotateCw, GamepadButton::RightTrigger),
+            (Action::Flip, GamepadButton::DPadUp),
+            (Action::StepPhysics, GamepadButton::DPadDown),
+            (Action::SlowDown, GamepadButton::DPadLeft),
+            (Action::SpeedUp, GamepadButton::DPadRight),
+            (Action::TogglePause, GamepadButton::Start),
+            (Action::CycleParticleView, GamepadButton::Select),
+        ]
+        .into_iter()
+        .map(|(action, button)| (action, vec![button]))
+        .collect()
+    }
+
+    /// Gives actions missing from the maps their default buttons.
+    pub fn fill_missing(&mut self) {
+        let mut defaults = Self::default_keys();
+        for action in Action::REBINDABLE {
+            let default = defaults.remove(&action).unwrap_or_default();
+            self.keys.entry(action).or_insert(default);
+        }
+        for (action, buttons) in Self::default_gamepad() {
+            self.gamepad.entry(action).or_insert(buttons);
+        }
+    }
 
-    input_map.insert(Action::RotateCcw, KeyCode::KeyQ);
-    input_map.insert(Action::RotateCw, KeyCode::KeyE);
-    input_map.insert(Action::Flip, KeyCode::KeyF);
-    input_map.insert(Action::TogglePhysics, KeyCode::Space);
+    /// What already uses `key`, if it can't be bound to `action`.
+    pub fn conflict(&self, action: Action, key: KeyCode) -> Option<&'static str> {
+        if PAN_KEYS.contains(&key) {
+            return Some(Action::Pan.label());
+        }
+        self.keys
+            .iter()
+            .find(|(other, keys)| **other != action && keys.contains(&key))
+            .map(|(other, _)| other.label())
+    }
+
+    /// The keys and gamepad buttons bound to `action`, named the way players read them.
+    pub fn button_names(&self, action: Action) -> Vec<String> {
+        let keys = self.keys.get(&action).into_iter().flatten();
+        let buttons = self.gamepad.get(&action).into_iter().flatten();
+        keys.map(|key| key_name(*key))
+            .chain(buttons.map(|button| format!("{button:?}")))
+            .collect()
+    }
+
+    /// Makes `key` the only key of `action`.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        self.keys.insert(action, vec![key]);
+    }
+
+    pub fn input_map(&self) -> InputMap<Action> {
+        let mut input_map = InputMap::default();
+        for (action, keys) in &self.keys {
+            for key in keys {
+                input_map.insert(*action, *key);
+            }
+        }
+        for (action, buttons) in &self.gamepad {
+            for button in buttons {
+                input_map.insert(*action, *button);
+            }
+        }
+        input_map.insert_dual_axis(Action::Pan, VirtualDPad::wasd());
+        input_map.insert_dual_axis(Action::Pan, VirtualDPad::arrow_keys());
+        input_map.insert_dual_axis(Action::Pan, GamepadStick::RIGHT.with_circle_deadzone(0.1));
+        input_map.insert_axis(Action::Zoom, MouseScrollAxis::Y);
+        input_map.insert_dual_axis(Action::Cursor, GamepadStick::LEFT.with_circle_deadzone(0.1));
+        input_map
+    }
+}
+
+/// While present, the keybinding screen is waiting for a key for this action and inputs
+/// aren't dispatched.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Rebinding(pub Action);
+
+/// `KeyQ` as `Q`, `Digit1` as `1`, anything else as its variant name.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    let short = name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name);
+    short.to_string()
+}
 
-    commands.spawn(input_map);
+fn spawn_player_input_map(mut commands: Commands, settings: Res<Settings>) {
+    commands.spawn(settings.keybinds.input_map());
 }
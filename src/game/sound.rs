@@ -1,26 +1,119 @@
-use crate::prelude::*;
+use crate::{
+    game::{physics::PhysicsState, replay::GameRng},
+    prelude::*,
+};
+use avian2d::prelude::*;
 use bevy::prelude::*;
-use bevy_seedling::{pool::Sampler, prelude::*};
+use bevy_seedling::{pool::Sampler, prelude::*, sample::Sample};
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub fn plugin(app: &mut App) {
     app.add_systems(OnExit(Screen::Gameplay), stop_soundtrack)
-        .add_systems(OnEnter(Screen::Gameplay), start_or_resume_soundtrack);
+        .add_systems(OnEnter(Screen::Gameplay), start_or_resume_soundtrack)
+        .add_systems(
+            Update,
+            (
+                enable_impact_events,
+                play_impact_sounds,
+                play_contact_sounds,
+                play_ambient_sounds,
+            )
+                .chain()
+                .run_if(in_state(Screen::Gameplay)),
+        )
+        .add_systems(
+            Update,
+            (
+                fade_music_layers.run_if(in_state(Screen::Gameplay)),
+                duck_under_modals,
+            ),
+        )
+        .add_systems(OnEnter(PhysicsState::Running), play_activation_sounds)
+        .add_observer(play_victory_stinger);
+}
+
+/// Stems of the gameplay soundtrack. They all start together so they stay in sync, and each
+/// is faded in and out by its cue. Until the recorded stems are in, every layer is the full
+/// track, so the mix only swells as the machine gets going.
+pub const MUSIC_LAYERS: &[MusicStem] = &[
+    MusicStem {
+        path: AudioSources::BG_MUSIC,
+        cue: MusicCue::Always,
+        gain: 0.6,
+    },
+    MusicStem {
+        path: AudioSources::BG_MUSIC,
+        cue: MusicCue::Running,
+        gain: 0.2,
+    },
+    MusicStem {
+        path: AudioSources::BG_MUSIC,
+        cue: MusicCue::CupsSatisfied(0.5),
+        gain: 0.2,
+    },
+];
+/// Seconds a music layer takes to fade fully in or out.
+const MUSIC_FADE_SECS: f32 = 2.0;
+
+/// General volume while a modal is open, relative to the set volume.
+const DUCKED_VOLUME: f32 = 0.4;
+/// Seconds the general volume takes to duck or come back.
+const DUCK_SECS: f32 = 0.3;
+
+/// One stem of the gameplay soundtrack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MusicStem {
+    pub path: &'static str,
+    pub cue: MusicCue,
+    /// Volume of the stem once faded in, on top of the music volume.
+    pub gain: f32,
+}
+
+/// When a music layer is heard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MusicCue {
+    Always,
+    /// While the simulation is running.
+    Running,
+    /// Once at least this fraction of the level's cups is satisfied.
+    CupsSatisfied(f32),
+}
+
+#[derive(Component)]
+struct MusicLayer {
+    stem: MusicStem,
+    /// How far the stem is faded in, from 0 to 1.
+    level: f32,
 }
 
 fn start_or_resume_soundtrack(
     mut cmds: Commands,
     settings: Res<Settings>,
-    sources: ResMut<AudioSources>,
-    mut music_query: Query<(&Sampler, &mut PlaybackSettings), With<Music>>,
+    sources: Res<AudioSources>,
+    mut layers: Query<(&Sampler, &mut PlaybackSettings), With<MusicLayer>>,
 ) -> Result {
-    if let Ok((player, mut instance)) = music_query.single_mut() {
+    if layers.is_empty() {
+        for (handle, stem) in sources.music_layers.iter().zip(MUSIC_LAYERS) {
+            let level = if stem.cue == MusicCue::Always {
+                1.0
+            } else {
+                0.0
+            };
+            cmds.spawn((
+                music_looping(handle.clone(), settings.music()),
+                MusicLayer { stem: *stem, level },
+                sample_effects![VolumeNode {
+                    volume: Volume::Linear(stem.gain * level),
+                    ..default()
+                }],
+            ));
+        }
+    }
+    for (player, mut instance) in &mut layers {
         if !player.is_playing() {
             instance.play();
         }
-    } else {
-        let handle = *[&sources.bg_music].choose(&mut thread_rng()).unwrap();
-        cmds.spawn(music_looping(handle.clone(), settings.music()));
     }
 
     Ok(())
@@ -31,3 +124,349 @@ fn stop_soundtrack(mut bg_music: Query<&mut PlaybackSettings, With<Music>>) {
         s.pause();
     }
 }
+
+fn fade_music_layers(
+    time: Res<Time>,
+    physics_state: Res<State<PhysicsState>>,
+    sensors: Query<Has<Satisfied>, With<TeaSensor>>,
+    mut layers: Query<(&mut MusicLayer, &SampleEffects)>,
+    mut volumes: Query<&mut VolumeNode>,
+) {
+    let running = *physics_state.get() == PhysicsState::Running;
+    let cups = sensors.iter().len();
+    let satisfied = sensors.iter().filter(|satisfied| *satisfied).count();
+    let satisfied = if cups > 0 {
+        satisfied as f32 / cups as f32
+    } else {
+        0.0
+    };
+
+    let step = time.delta_secs() / MUSIC_FADE_SECS;
+    for (mut layer, effects) in &mut layers {
+        let heard = match layer.stem.cue {
+            MusicCue::Always => true,
+            MusicCue::Running => running,
+            MusicCue::CupsSatisfied(fraction) => cups > 0 && satisfied >= fraction,
+        };
+        let target = if heard { 1.0 } else { 0.0 };
+        let level = layer.level + (target - layer.level).clamp(-step, step);
+        if level == layer.level {
+            continue;
+        }
+        layer.level = level;
+        for effect in effects.iter() {
+            if let Ok(mut volume) = volumes.get_mut(effect) {
+                volume.volume = Volume::Linear(layer.stem.gain * level);
+            }
+        }
+    }
+}
+
+/// Plays when `check_tea_counters` finishes a level or the game.
+fn play_victory_stinger(
+    trigger: Trigger<OnNewModal>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    sources: Res<AudioSources>,
+) {
+    if matches!(trigger.0, Modal::LevelFinished | Modal::GameFinished) {
+        commands.spawn(music(sources.victory_stinger.clone(), settings.music()));
+    }
+}
+
+/// Lowers the [`General`] bus while a modal is open.
+fn duck_under_modals(
+    time: Res<Time>,
+    screen: Res<State<Screen>>,
+    settings: Res<Settings>,
+    mut ducked: Local<f32>,
+    mut general: Single<&mut VolumeNode, With<General>>,
+) {
+    let modal_open = *screen.get() == Screen::Gameplay && !settings.modals.is_empty();
+    let target = if modal_open { 1.0 } else { 0.0 };
+    let step = time.delta_secs() / DUCK_SECS;
+    let next = *ducked + (target - *ducked).clamp(-step, step);
+    if next == *ducked && !settings.is_changed() {
+        return;
+    }
+    *ducked = next;
+    let duck = 1.0 - next * (1.0 - DUCKED_VOLUME);
+    general.volume = Volume::Linear(settings.sound.general * duck);
+}
+
+/// Impacts slower than this, in world units per second, make no sound.
+const QUIET_IMPACT_SPEED: f32 = 40.0;
+/// Impacts at this speed and above play at full volume.
+const LOUD_IMPACT_SPEED: f32 = 400.0;
+/// Impact sounds started per frame, the loudest ones win. A cup filling up would otherwise
+/// take every voice of the [`Sfx`] pool.
+const MAX_IMPACTS_PER_FRAME: usize = 3;
+
+/// What a collider sounds like when something hits it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImpactMaterial {
+    /// A particle, brewed or not.
+    Liquid {
+        tea: bool,
+    },
+    Ball,
+    Cup,
+    Solid,
+}
+
+/// When a part's one-shot sound plays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum SoundTrigger {
+    /// When the simulation starts.
+    #[default]
+    Activation,
+    /// When something hits the part.
+    Contact,
+}
+
+/// Sounds of a part, spawned from its `Sound` subassembly.
+#[derive(Component, Debug, Clone)]
+pub struct PartSound {
+    pub ambient: Option<Handle<Sample>>,
+    pub one_shot: Option<Handle<Sample>>,
+    pub play_on: SoundTrigger,
+    pub volume: f32,
+    pub pitch: (f32, f32),
+    pub while_running: bool,
+}
+
+impl PartSound {
+    fn speed(&self, rng: &mut impl Rng) -> f64 {
+        let (low, high) = self.pitch;
+        if low < high {
+            rng.gen_range(low..=high) as f64
+        } else {
+            low as f64
+        }
+    }
+}
+
+/// The looping sample currently playing for a [`PartSound`].
+#[derive(Component)]
+struct AmbientVoice(Entity);
+
+/// Only colliders with [`CollisionEventsEnabled`] report impacts, particles hitting each other
+/// stay silent that way.
+fn enable_impact_events(
+    mut commands: Commands,
+    colliders: Query<(Entity, &ColliderOf), Added<ColliderOf>>,
+    loud: Query<(), Or<(With<RedBall>, With<TeaSensor>)>>,
+    children: Query<&Children>,
+    part_sounds: Query<&PartSound>,
+) {
+    for (collider, collider_of) in &colliders {
+        let body = collider_of.body;
+        let contact_sound = children.iter_descendants(body).any(|child| {
+            part_sounds
+                .get(child)
+                .is_ok_and(|sound| sound.play_on == SoundTrigger::Contact)
+        });
+        if loud.contains(collider) || loud.contains(body) || contact_sound {
+            commands.entity(collider).insert(CollisionEventsEnabled);
+        }
+    }
+}
+
+/// How loud an impact at this speed is, from 0 for [`QUIET_IMPACT_SPEED`] and below to 1.
+fn impact_loudness(speed: f32) -> f32 {
+    ((speed - QUIET_IMPACT_SPEED) / (LOUD_IMPACT_SPEED - QUIET_IMPACT_SPEED)).clamp(0.0, 1.0)
+}
+
+fn play_impact_sounds(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut started: EventReader<CollisionStarted>,
+    settings: Res<Settings>,
+    sources: Res<AudioSources>,
+    colliders: Query<&ColliderOf>,
+    velocities: Query<&LinearVelocity>,
+    transforms: Query<&GlobalTransform>,
+    particles: Query<&Particle>,
+    balls: Query<(), With<RedBall>>,
+    cups: Query<(), With<TeaSensor>>,
+) {
+    let material = |collider: Entity| {
+        let body = colliders.get(collider).map_or(collider, |c| c.body);
+        if let Ok(particle) = particles.get(body) {
+            ImpactMaterial::Liquid {
+                tea: particle.contents.is_tea(),
+            }
+        } else if cups.contains(collider) {
+            ImpactMaterial::Cup
+        } else if balls.contains(body) {
+            ImpactMaterial::Ball
+        } else {
+            ImpactMaterial::Solid
+        }
+    };
+    let velocity = |collider: Entity| {
+        let body = colliders.get(collider).map_or(collider, |c| c.body);
+        velocities.get(body).map_or(Vec2::ZERO, |v| v.0)
+    };
+
+    let mut impacts: Vec<_> = started
+        .read()
+        .filter_map(|CollisionStarted(a, b)| {
+            let sources = impact_sources(&sources, material(*a), material(*b))?;
+            let loudness = impact_loudness((velocity(*a) - velocity(*b)).length());
+            let at = transforms.get(*a).map_or(Vec3::ZERO, |t| t.translation());
+            (loudness > 0.0).then_some((sources, loudness, at))
+        })
+        .collect();
+    impacts.sort_by(|(_, a, _), (_, b, _)| b.total_cmp(a));
+
+    let rng = &mut rng.audio;
+    for (sources, loudness, at) in impacts.into_iter().take(MAX_IMPACTS_PER_FRAME) {
+        let Some(handle) = sources.choose(rng) else {
+            continue;
+        };
+        // harder hits sound a little higher
+        let speed = 0.9 + 0.2 * loudness as f64 + rng.gen_range(-0.05..0.05);
+        commands.spawn((
+            spatial_sfx(handle.clone(), settings.sfx() * loudness, at),
+            PlaybackSettings { speed, ..default() },
+        ));
+    }
+}
+
+/// Samples for a hit between two materials, `None` for pairs that make no sound.
+fn impact_sources(
+    sources: &AudioSources,
+    a: ImpactMaterial,
+    b: ImpactMaterial,
+) -> Option<&[Handle<Sample>]> {
+    use ImpactMaterial::*;
+    match (a, b) {
+        (Liquid { tea: true }, Cup) | (Cup, Liquid { tea: true }) => {
+            Some(std::slice::from_ref(&sources.cup_drop_brewed))
+        }
+        (Liquid { .. }, Cup) | (Cup, Liquid { .. }) => Some(&sources.into_cup_plops),
+        (Ball, Cup) | (Cup, Ball) => Some(std::slice::from_ref(&sources.cup_drop)),
+        (Ball, Solid | Ball) | (Solid, Ball) => Some(&sources.pipe_hits),
+        _ => None,
+    }
+}
+
+/// Plays the one-shot of parts with [`SoundTrigger::Contact`] when something hits them, once
+/// per part and frame.
+fn play_contact_sounds(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut started: EventReader<CollisionStarted>,
+    settings: Res<Settings>,
+    colliders: Query<&ColliderOf>,
+    velocities: Query<&LinearVelocity>,
+    children: Query<&Children>,
+    part_sounds: Query<(&PartSound, &GlobalTransform)>,
+) {
+    let body = |collider: Entity| colliders.get(collider).map_or(collider, |c| c.body);
+    let velocity = |body: Entity| velocities.get(body).map_or(Vec2::ZERO, |v| v.0);
+
+    let mut played = vec![];
+    let rng = &mut rng.audio;
+    for CollisionStarted(a, b) in started.read() {
+        let (a, b) = (body(*a), body(*b));
+        let loudness = impact_loudness((velocity(a) - velocity(b)).length());
+        if loudness <= 0.0 {
+            continue;
+        }
+        for part in [a, b] {
+            for child in children.iter_descendants(part) {
+                let Ok((sound, transform)) = part_sounds.get(child) else {
+                    continue;
+                };
+                let Some(one_shot) = &sound.one_shot else {
+                    continue;
+                };
+                if sound.play_on != SoundTrigger::Contact
+                    || played.contains(&child)
+                    || played.len() >= MAX_IMPACTS_PER_FRAME
+                {
+                    continue;
+                }
+                played.push(child);
+                commands.spawn((
+                    spatial_sfx(
+                        one_shot.clone(),
+                        settings.sfx() * sound.volume * loudness,
+                        transform.translation(),
+                    ),
+                    PlaybackSettings {
+                        speed: sound.speed(rng),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn play_activation_sounds(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    settings: Res<Settings>,
+    part_sounds: Query<(&PartSound, &GlobalTransform)>,
+) {
+    let rng = &mut rng.audio;
+    for (sound, transform) in &part_sounds {
+        let Some(one_shot) = &sound.one_shot else {
+            continue;
+        };
+        if sound.play_on == SoundTrigger::Activation {
+            commands.spawn((
+                spatial_sfx(
+                    one_shot.clone(),
+                    settings.sfx() * sound.volume,
+                    transform.translation(),
+                ),
+                PlaybackSettings {
+                    speed: sound.speed(rng),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+/// Starts and stops the looping sample of each [`PartSound`], following the physics state
+/// for the ones that only hum `while_running`.
+fn play_ambient_sounds(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    settings: Res<Settings>,
+    physics_state: Res<State<PhysicsState>>,
+    part_sounds: Query<(Entity, &PartSound, Option<&AmbientVoice>)>,
+) {
+    let running = *physics_state.get() == PhysicsState::Running;
+    let rng = &mut rng.audio;
+    for (entity, sound, voice) in &part_sounds {
+        let Some(ambient) = &sound.ambient else {
+            continue;
+        };
+        match (running || !sound.while_running, voice) {
+            (true, None) => {
+                let voice = commands
+                    .spawn((
+                        spatial_sfx_looping(ambient.clone(), settings.sfx() * sound.volume),
+                        PlaybackSettings {
+                            speed: sound.speed(rng),
+                            ..default()
+                        },
+                        ChildOf(entity),
+                    ))
+                    .id();
+                commands.entity(entity).insert(AmbientVoice(voice));
+            }
+            (false, Some(AmbientVoice(voice))) => {
+                commands.entity(*voice).despawn();
+                commands.entity(entity).remove::<AmbientVoice>();
+            }
+            _ => {}
+        }
+    }
+}
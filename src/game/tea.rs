@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::{game::environment::Environment, prelude::*};
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -6,39 +6,58 @@ use serde::{Deserialize, Serialize};
 pub fn plugin(app: &mut App) {
     app.add_systems(
         Update,
-        (apply_tea, update_tea_sensors)
-            .run_if(resource_exists::<Config>)
-            .run_if(resource_exists::<AudioSources>),
+        (apply_tea, update_tea_sensors).run_if(resource_exists::<Environment>),
     );
 }
 
 #[derive(Component, Copy, Clone, PartialEq)]
 pub struct Tea;
 #[derive(Default, Component, Clone, PartialEq)]
+#[require(SensorReading)]
 pub struct TeaSensor(pub Recipe, pub Handle<Image>);
+/// What a [`TeaSensor`] currently holds, refreshed every frame.
+#[derive(Default, Component, Debug, Clone, Copy, PartialEq)]
+pub struct SensorReading {
+    pub count: usize,
+    pub average: ParticleContents,
+}
 #[derive(Component)]
 pub struct Satisfied;
 
 #[derive(Component, Debug, Default, Copy, Clone, PartialEq, Reflect, Serialize, Deserialize)]
 pub struct Recipe {
-    milky: bool,
-    sweet: bool,
+    pub milky: bool,
+    pub sweet: bool,
+}
+
+/// Particles a [`TeaSensor`] needs to hold before its recipe is checked.
+pub const MIN_SENSOR_PARTICLES: usize = 10;
+
+impl Recipe {
+    pub fn is_satisfied_by(&self, reading: &SensorReading) -> bool {
+        let avg = reading.average;
+        reading.count >= MIN_SENSOR_PARTICLES
+            && avg.is_tea()
+            && avg.is_milky() == self.milky
+            && avg.is_sweet() == self.sweet
+    }
 }
 
 fn apply_tea(
     collisions: Collisions,
     tea: Query<Entity, With<Tea>>,
     mut particles: Query<(Entity, &mut Particle)>,
-    config: Res<Config>,
+    environment: Res<Environment>,
     time: Res<Time<Physics>>,
 ) {
     for tea_entity in &tea {
         for (particle_entity, mut particle) in &mut particles {
-            if particle.contents.heat < config.physics.brewing_temperature {
+            if particle.contents.heat < environment.brewing_temperature {
                 continue;
             }
             if collisions.contains(tea_entity, particle_entity) {
-                particle.contents.tea = (particle.contents.tea + time.delta().as_secs_f32() * 50.0).min(1.0);
+                particle.contents.tea =
+                    (particle.contents.tea + time.delta().as_secs_f32() * 50.0).min(1.0);
             }
         }
     }
@@ -47,10 +66,10 @@ fn apply_tea(
 fn update_tea_sensors(
     collisions: Collisions,
     mut commands: Commands,
-    tea_sensors: Query<(Entity, &TeaSensor)>,
+    mut tea_sensors: Query<(Entity, &TeaSensor, &mut SensorReading, Has<Satisfied>)>,
     particles: Query<(Entity, &Particle)>,
 ) {
-    for (sensor_entity, sensor) in &tea_sensors {
+    for (sensor_entity, sensor, mut reading, was_satisfied) in &mut tea_sensors {
         let mut total = ParticleContents::default();
         let mut count = 0;
         for (particle_entity, particle) in &particles {
@@ -59,14 +78,18 @@ fn update_tea_sensors(
                 count += 1;
             }
         }
+        let average = if count > 0 {
+            total / count as f32
+        } else {
+            total
+        };
+        reading.set_if_neq(SensorReading { count, average });
 
-        commands.entity(sensor_entity).remove::<Satisfied>();
-        if count >= 10 {
-            let avg = total / count as f32;
-            if avg.is_tea() && avg.is_milky() == sensor.0.milky && avg.is_sweet() == sensor.0.sweet
-            {
-                commands.entity(sensor_entity).insert(Satisfied);
-            }
+        let satisfied = sensor.0.is_satisfied_by(&reading);
+        if satisfied && !was_satisfied {
+            commands.entity(sensor_entity).insert(Satisfied);
+        } else if !satisfied && was_satisfied {
+            commands.entity(sensor_entity).remove::<Satisfied>();
         }
     }
 }
@@ -1,13 +1,13 @@
-use bevy::prelude::*;
-use avian2d::prelude::*;
 use crate::{
-    prelude::*,
-    game::tea::Tea,
     game::ParticleLayer,
+    game::tea::Tea,
     prelude::Config,
+    prelude::*,
     screens::Screen,
-    game::machine_parts::particle_vessel::scan_image_for_circles,
+    util::image_geometry::{RgbaBuffer, scan_image_for_circles},
 };
+use avian2d::prelude::*;
+use bevy::prelude::*;
 
 pub fn plugin(app: &mut App) {
     app.add_systems(
@@ -45,14 +45,13 @@ pub fn do_particle_vessels(
             continue;
         };
 
-        let relative_positions = scan_image_for_circles(img, vessel.particle_radius as i32);
+        let image = RgbaBuffer::from_image(img);
+        let relative_positions = scan_image_for_circles(&image, vessel.particle_radius as i32);
         // let relative_positions = vec![IVec2::new(0.0,m, y)]
 
-
         for position in relative_positions {
             let spawn_position = global_transform.translation().truncate() + position.as_vec2();
 
-
             commands.spawn((
                 Sprite::from_image(vessel.particle_image.clone()),
                 LevelObject,
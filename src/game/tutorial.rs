@@ -1,74 +1,226 @@
+//! Tutorial steps from `tutorial.ron`.
+//!
+//! Steps without a level are the intro, shown on the first level played. Steps bound to a
+//! level are shown the first time that level is entered. A step that waits for
+//! [SPACE] is a modal, any other step is a banner that lets the player do what it asks.
+
 use super::*;
-use crate::loading::{LoadResource, RonAssetPlugin};
+use crate::{
+    game::physics::PhysicsState,
+    game::settings::Rebinding,
+    game::tea::{Satisfied, TeaSensor},
+    loading::{LoadResource, validation::ContentIssue},
+};
 use bevy::{
     asset::{AssetLoader, LoadContext, io::Reader},
+    platform::collections::HashSet,
     ui::Val::*,
 };
 use leafwing_input_manager::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use thiserror::Error;
 
 pub(super) fn plugin(app: &mut App) {
-    app.init_state::<Step>();
-    app.enable_state_scoped_entities::<Step>();
-
-    app.add_plugins(RonAssetPlugin::<Tutorial>::new(&["tutorial.ron"]))
+    app.init_resource::<TutorialProgress>()
         .register_asset_loader(TutorialListLoader)
         .load_resource_from_path::<Tutorial>("tutorial.ron");
 
-    app.add_systems(OnEnter(Screen::Gameplay), render_step)
-        // .add_systems(OnEnter(Screen::Title), reset_tutorial)
-        .add_systems(
-            Update,
-            (next_step, render_step.run_if(state_changed::<Step>))
-                .run_if(resource_exists::<Tutorial>.and(in_state(Screen::Gameplay))),
-        );
+    app.add_systems(
+        Update,
+        (
+            queue_steps.run_if(resource_exists_and_changed::<LoadedLevel>),
+            render_step.run_if(resource_changed::<TutorialProgress>),
+            highlight_anchors,
+            draw_world_highlight,
+        )
+            .chain()
+            .run_if(resource_exists::<Tutorial>.and(in_state(Screen::Gameplay))),
+    )
+    // after Update, so the [SPACE] that closes a modal doesn't also start the physics
+    .add_systems(
+        PostUpdate,
+        advance_step
+            .run_if(resource_exists::<Tutorial>.and(in_state(Screen::Gameplay)))
+            .run_if(not(resource_exists::<Rebinding>)),
+    );
+}
+
+#[derive(Default, Asset, Resource, Reflect, Clone, Debug)]
+pub struct Tutorial(pub Vec<TutorialStep>);
+
+#[derive(Clone, Debug, Reflect, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TutorialStep {
+    pub text: String,
+    /// Asset path of a picture shown with the text.
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub highlight: Option<Highlight>,
+    #[serde(default)]
+    pub advance: Advance,
+    /// [`LevelConfig::name`] of the level this step is shown on.
+    #[serde(default)]
+    pub level: Option<String>,
+    #[serde(skip)]
+    pub loaded_image: Option<Handle<Image>>,
 }
 
-#[derive(Default, Asset, Resource, Reflect, Clone, Debug, Serialize, Deserialize)]
-pub struct Tutorial(pub Vec<String>);
+#[derive(Clone, Debug, Reflect, Serialize, Deserialize)]
+pub enum Highlight {
+    /// Every UI element with this [`TutorialAnchor`].
+    Ui(String),
+    /// A point in the level.
+    World(Vec2),
+}
 
-#[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
-struct Step(usize);
+/// What moves the tutorial past a step.
+#[derive(Default, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum Advance {
+    /// Pressing [SPACE].
+    #[default]
+    Continue,
+    /// Placing a part with this name.
+    PlacePart(String),
+    StartPhysics,
+    /// Any tea sensor getting what its recipe asks for.
+    SensorSatisfied,
+}
+
+impl Advance {
+    fn hint(&self, localization: &Localization, parts: &MachinePartConfigByType) -> String {
+        match self {
+            Advance::Continue => localization.get("[SPACE] to continue"),
+            Advance::PlacePart(name) => {
+                let key = parts
+                    .0
+                    .get(name)
+                    .map_or(name.as_str(), |c| c.display_key(name));
+                localization.format("Place a {part}", &[("part", &localization.get(key))])
+            }
+            Advance::StartPhysics => localization.get("Press [SPACE] or the play button to start"),
+            Advance::SensorSatisfied => localization.get("Fill a cup with what it asks for"),
+        }
+    }
+}
+
+/// Lets a [`Highlight::Ui`] point at this UI element.
+#[derive(Component, Clone, Debug)]
+pub struct TutorialAnchor(pub String);
+
+impl TutorialAnchor {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TutorialProgress {
+    /// Indices of the steps still to show. The first one is on screen.
+    queue: VecDeque<usize>,
+    /// The intro (`None`) and the levels whose steps were queued already.
+    seen: HashSet<Option<String>>,
+}
+
+impl TutorialProgress {
+    fn current<'a>(&self, tutorial: &'a Tutorial) -> Option<&'a TutorialStep> {
+        self.queue.front().and_then(|index| tutorial.0.get(*index))
+    }
+}
+
+/// Run condition that is true while a step waiting for [SPACE] is on screen.
+pub fn tutorial_modal_open(
+    progress: Res<TutorialProgress>,
+    tutorial: Option<Res<Tutorial>>,
+) -> bool {
+    tutorial.is_some_and(|tutorial| {
+        progress
+            .current(&tutorial)
+            .is_some_and(|step| step.advance == Advance::Continue)
+    })
+}
 
 #[derive(Component)]
 pub struct TutorialModal;
+#[derive(Component)]
+struct Highlighted;
+
+fn queue_steps(
+    mut progress: ResMut<TutorialProgress>,
+    tutorial: Res<Tutorial>,
+    settings: Res<Settings>,
+    in_editor: Res<EditorMode>,
+    loaded_level: Res<LoadedLevel>,
+    level_configs: Res<Assets<LevelConfig>>,
+) {
+    if !settings.tutorial || in_editor.0 {
+        return;
+    }
+    let level = level_configs
+        .get(&loaded_level.0)
+        .map(|level| level.name.clone());
 
-// fn reset_tutorial(mut settings: ResMut<Settings>, mut step: ResMut<NextState<Step>>) {
-//     settings.tutorial = true;
-//     step.set(Step(0));
-// }
+    // tips for the level the player just left don't apply anymore
+    progress.queue.retain(|index| {
+        let step_level = &tutorial.0[*index].level;
+        step_level.is_none() || *step_level == level
+    });
+    for key in [None, level] {
+        if !progress.seen.insert(key.clone()) {
+            continue;
+        }
+        let steps = tutorial.0.iter().enumerate();
+        let indices: Vec<_> = steps
+            .filter(|(_, step)| step.level == key)
+            .map(|(index, _)| index)
+            .collect();
+        progress.queue.extend(indices);
+    }
+}
 
 fn render_step(
-    step: Res<State<Step>>,
     mut commands: Commands,
-    text: Res<Tutorial>,
-    textures: Res<Textures>,
-    in_editor: Res<EditorMode>,
+    progress: Res<TutorialProgress>,
+    tutorial: Res<Tutorial>,
+    localization: Localization,
+    parts: Res<MachinePartConfigByType>,
+    modals: Query<Entity, With<TutorialModal>>,
+    highlighted: Query<Entity, With<Highlighted>>,
 ) {
-    if step.0 > 6 || in_editor.0 {
-        return;
+    for entity in &modals {
+        commands.entity(entity).despawn();
+    }
+    for entity in &highlighted {
+        commands.entity(entity).remove::<(Outline, Highlighted)>();
     }
 
-    let txt = text.0[step.0].clone();
-    match step.0 {
-        0 => {
-            commands.spawn((
-                StateScoped(step.clone()),
-                tutorial_modal(txt),
-                ImageNode::from(textures.chill.clone()),
-            ));
+    let Some(step) = progress.current(&tutorial) else {
+        return;
+    };
+    let image = step.loaded_image.clone().map(ImageNode::new);
+    if step.advance == Advance::Continue {
+        let modal = commands.spawn(tutorial_modal(step.text.clone())).id();
+        if let Some(image) = image {
+            commands.entity(modal).insert(image);
         }
-        1 => {
-            commands.spawn((
-                StateScoped(step.clone()),
-                tutorial_modal(txt),
-                ImageNode::from(textures.kidnap.clone()),
+    } else {
+        let banner = commands
+            .spawn(tutorial_banner(
+                step.text.clone(),
+                step.advance.hint(&localization, &parts),
+            ))
+            .id();
+        if let Some(image) = image {
+            commands.entity(banner).with_child((
+                image,
+                Node {
+                    height: Vw(8.0),
+                    ..default()
+                },
+                Pickable::IGNORE,
             ));
         }
-        _ => {
-            commands.spawn((StateScoped(step.clone()), tutorial_modal(txt)));
-        }
     }
 }
 
@@ -99,30 +251,126 @@ fn tutorial_modal(s: String) -> impl Bundle {
             BackgroundColor(Color::srgba_u8(58, 68, 103, 240)),
             BorderColor(WHITEISH),
             BorderRadius::all(Px(BORDER_RADIUS)),
-            children![label(s), label("\n[SPACE] to continue")]
+            children![
+                label(s),
+                (
+                    Node {
+                        margin: UiRect::top(Px(FONT_SIZE)),
+                        ..default()
+                    },
+                    children![label("[SPACE] to continue")],
+                ),
+            ]
         )],
     )
 }
 
-fn next_step(
-    action: Query<&ActionState<Action>>,
+/// A step the player acts on, kept out of the way and out of picking.
+fn tutorial_banner(s: String, hint: String) -> impl Bundle {
+    (
+        StateScoped(Screen::Gameplay),
+        Name::new("Tutorial"),
+        TutorialModal,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Vw(11.0),
+            left: Percent(25.0),
+            width: Percent(50.0),
+            padding: UiRect::all(Px(8.0)),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba_u8(58, 68, 103, 200)),
+        BorderRadius::all(Px(BORDER_RADIUS)),
+        Pickable::IGNORE,
+        children![label(s), label(hint)],
+    )
+}
+
+/// Outlines the anchors of the current step, including ones spawned after it was shown.
+fn highlight_anchors(
     mut commands: Commands,
+    progress: Res<TutorialProgress>,
+    tutorial: Res<Tutorial>,
+    anchors: Query<(Entity, &TutorialAnchor), Without<Highlighted>>,
+) {
+    let Some(Highlight::Ui(name)) = progress
+        .current(&tutorial)
+        .and_then(|step| step.highlight.as_ref())
+    else {
+        return;
+    };
+    for (entity, anchor) in &anchors {
+        if anchor.0 == *name {
+            commands
+                .entity(entity)
+                .insert((Highlighted, Outline::new(Px(3.0), Px(2.0), YELLOW)));
+        }
+    }
+}
+
+fn draw_world_highlight(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    progress: Res<TutorialProgress>,
+    tutorial: Res<Tutorial>,
+) {
+    if let Some(Highlight::World(position)) = progress
+        .current(&tutorial)
+        .and_then(|step| step.highlight.as_ref())
+    {
+        let radius = 24.0 + 4.0 * (time.elapsed_secs() * 4.0).sin();
+        gizmos.circle_2d(*position, radius, YELLOW);
+    }
+}
+
+/// Moves past the current step once it's done, and turns the tutorial off once every step
+/// has been shown, so it doesn't start over on the next launch.
+fn advance_step(
+    mut progress: ResMut<TutorialProgress>,
+    tutorial: Res<Tutorial>,
     mut settings: ResMut<Settings>,
-    current: ResMut<State<Step>>,
-    mut next: ResMut<NextState<Step>>,
+    action: Query<&ActionState<Action>>,
+    physics_state: Res<State<PhysicsState>>,
+    satisfied: Query<(), (With<TeaSensor>, With<Satisfied>)>,
+    mut part_requests: EventReader<MachinePartRequest>,
 ) {
-    if let Ok(state) = action.single() {
-        if state.just_pressed(&Action::TogglePhysics) {
-            if settings.tutorial {
-                if current.0 >= 6 {
-                    settings.tutorial = false;
-                    if !settings.modals.is_empty() {
-                        commands.trigger(OnPopModal);
-                    }
-                }
-
-                next.set(Step(current.0 + 1));
+    if !settings.tutorial {
+        if !progress.queue.is_empty() {
+            progress.queue.clear();
+        }
+        return;
+    }
+    let placed: Vec<_> = part_requests
+        .read()
+        .filter_map(|request| match request {
+            MachinePartRequest::SpawnMachinePart(spawn) if !spawn.initial_part => {
+                Some(spawn.part_type.name.clone())
             }
+            _ => None,
+        })
+        .collect();
+    let Some(step) = progress.current(&tutorial) else {
+        return;
+    };
+
+    let done = match &step.advance {
+        Advance::Continue => action
+            .single()
+            .is_ok_and(|state| state.just_pressed(&Action::TogglePhysics)),
+        Advance::PlacePart(name) => placed.contains(name),
+        Advance::StartPhysics => *physics_state.get() == PhysicsState::Running,
+        Advance::SensorSatisfied => !satisfied.is_empty(),
+    };
+    if done {
+        progress.queue.pop_front();
+        let all_seen = tutorial
+            .0
+            .iter()
+            .all(|step| progress.seen.contains(&step.level));
+        if progress.queue.is_empty() && all_seen {
+            settings.tutorial = false;
         }
     }
 }
@@ -136,9 +384,9 @@ enum TutorialListLoaderError {
     /// An [IO](std::io) Error
     #[error("Could not load asset: {0}")]
     Io(#[from] std::io::Error),
-    /// A [RON](ron) Error
-    #[error("Could not parse RON: {0}")]
-    RonSpannedError(#[from] ron::error::SpannedError),
+    /// A [RON](ron) Error pointed at the file and line
+    #[error(transparent)]
+    Content(#[from] ContentIssue),
     #[error(transparent)]
     LoadDirectError(#[from] bevy::asset::LoadDirectError),
 }
@@ -151,11 +399,16 @@ impl AssetLoader for TutorialListLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &(),
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let steps = ron::de::from_bytes::<Vec<String>>(&bytes)?;
+        let mut steps = ron::de::from_bytes::<Vec<TutorialStep>>(&bytes)
+            .map_err(|e| ContentIssue::from_ron(&load_context.path().to_string_lossy(), &e))?;
+
+        for step in &mut steps {
+            step.loaded_image = step.image.as_ref().map(|path| load_context.load(path));
+        }
 
         Ok(Tutorial(steps))
     }
@@ -1,17 +1,21 @@
-use crate::prelude::*;
+use crate::{game::sound::MUSIC_LAYERS, prelude::*};
 use bevy::{asset::Asset, prelude::*};
 use bevy_seedling::sample::Sample;
 
+pub mod packs;
 mod ron;
 pub mod textures;
 mod tracking;
+pub mod validation;
 
 pub use ron::*;
 pub use tracking::*;
 
 pub fn plugin(app: &mut App) {
+    #[cfg(all(feature = "dev_native", not(target_arch = "wasm32")))]
+    app.add_plugins(validation::plugin);
     // start asset loading
-    app.add_plugins(tracking::plugin)
+    app.add_plugins((tracking::plugin, packs::plugin))
         .add_plugins(RonAssetPlugin::<Config>::new(&["config.ron"]))
         .load_resource_from_path::<Config>("config.ron")
         .load_resource::<AudioSources>()
@@ -45,10 +49,6 @@ pub struct Textures {
     pub redo: Handle<Image>,
     #[dependency]
     pub reset: Handle<Image>,
-    #[dependency]
-    pub kidnap: Handle<Image>,
-    #[dependency]
-    pub chill: Handle<Image>,
 }
 
 impl FromWorld for Textures {
@@ -63,8 +63,6 @@ impl FromWorld for Textures {
             play: assets.load("textures/Icons/play.png"),
             redo: assets.load("textures/Icons/redo.png"),
             reset: assets.load("textures/Icons/reset.png"),
-            kidnap: assets.load("textures/tutorial1.jpg"),
-            chill: assets.load("textures/chai-reaction-embedded-thumbnail.jpg"),
         }
     }
 }
@@ -89,12 +87,15 @@ pub struct AudioSources {
     pub pipe_hits: Vec<Handle<Sample>>,
     #[dependency]
     pub into_cup_plops: Vec<Handle<Sample>>,
-    #[dependency]
-    pub stove_looping: Handle<Sample>,
 
     // music
     #[dependency]
     pub bg_music: Handle<Sample>,
+    /// One per [`MUSIC_LAYERS`] entry.
+    #[dependency]
+    pub music_layers: Vec<Handle<Sample>>,
+    #[dependency]
+    pub victory_stinger: Handle<Sample>,
     #[dependency]
     pub menu: Handle<Sample>,
 }
@@ -114,10 +115,11 @@ impl AudioSources {
     ];
     pub const INTO_CUP_PLOPS: &'static [&'static str] =
         &["audio/sfx/intoTheCup1.ogg", "audio/sfx/intoTheCup2.ogg"];
-    pub const STOVE_LOOPING: &'static str = "audio/sfx/stoveLoopingBitcrushed.ogg";
 
     pub const BG_MUSIC: &'static str = "audio/music/lofi-tea.ogg";
     pub const MENU: &'static str = "audio/music/how-to-make-tea.ogg";
+    /// Stands in until the soundtrack gets a stinger of its own.
+    pub const VICTORY_STINGER: &'static str = "audio/sfx/plop.ogg";
 }
 
 impl FromWorld for AudioSources {
@@ -126,6 +128,11 @@ impl FromWorld for AudioSources {
         Self {
             menu: assets.load(Self::MENU),
             bg_music: assets.load(Self::BG_MUSIC),
+            music_layers: MUSIC_LAYERS
+                .iter()
+                .map(|stem| assets.load(stem.path))
+                .collect(),
+            victory_stinger: assets.load(Self::VICTORY_STINGER),
             btn_hover: assets.load(Self::BTN_HOVER),
             btn_press: assets.load(Self::BTN_PRESS),
             cup_drop: assets.load(Self::CUP_DROP),
@@ -137,7 +144,6 @@ impl FromWorld for AudioSources {
                 .iter()
                 .map(|p| assets.load(*p))
                 .collect(),
-            stove_looping: assets.load(Self::STOVE_LOOPING),
         }
     }
 }
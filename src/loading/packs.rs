@@ -0,0 +1,140 @@
+//! Content packs shared by players, found in the `packs` folder of the user config directory.
+//!
+//! Each pack is a folder with a `machine_parts.ron`, a `levels.ron`, or both, laid out like
+//! the `assets` folder. Paths inside are relative to the file that names them. Pack parts join
+//! the part library unless their name is already taken, pack levels are a campaign of their own
+//! on the title screen.
+
+use crate::{prelude::*, util::user_dir};
+use bevy::{
+    asset::io::AssetSourceBuilder,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use std::path::{Path, PathBuf};
+
+/// Asset source the packs are read from, `packs://<pack>/levels.ron`.
+const PACKS_SOURCE: &str = "packs";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ContentPacks>()
+        .add_systems(Startup, load_packs)
+        .add_systems(
+            Update,
+            merge_pack_parts.run_if(resource_exists::<MachinePartConfigByType>),
+        );
+}
+
+/// Makes the packs folder readable by the asset server. Asset sources have to be registered
+/// before the `AssetPlugin`.
+pub fn register_source(app: &mut App) {
+    let Some(dir) = packs_dir().filter(|dir| dir.is_dir()) else {
+        return;
+    };
+    app.register_asset_source(
+        PACKS_SOURCE,
+        AssetSourceBuilder::platform_default(&dir.to_string_lossy(), None),
+    );
+}
+
+fn packs_dir() -> Option<PathBuf> {
+    user_dir::config_dir().map(|dir| dir.join("packs"))
+}
+
+#[derive(Resource, Default)]
+pub struct ContentPacks {
+    pub packs: Vec<ContentPack>,
+    /// The game's own campaign, to go back to after playing a pack.
+    pub base_levels: Handle<LevelList>,
+    /// The pack each merged part came from.
+    merged: HashMap<String, String>,
+    /// Name collisions that were already logged.
+    reported: HashSet<(String, String)>,
+}
+
+pub struct ContentPack {
+    /// Name of the pack's folder.
+    pub name: String,
+    pub parts: Option<Handle<MachinePartConfigByType>>,
+    pub levels: Option<Handle<LevelList>>,
+}
+
+fn load_packs(assets: Res<AssetServer>, mut packs: ResMut<ContentPacks>) {
+    packs.base_levels = assets.load("levels.ron");
+
+    let Some(dir) = packs_dir().filter(|dir| dir.is_dir()) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    let mut names: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+
+    for name in names {
+        let pack = ContentPack {
+            parts: load_pack_file(&assets, &dir, &name, "machine_parts.ron"),
+            levels: load_pack_file(&assets, &dir, &name, "levels.ron"),
+            name,
+        };
+        if pack.parts.is_none() && pack.levels.is_none() {
+            warn!(
+                "content pack {} has no machine_parts.ron or levels.ron",
+                pack.name
+            );
+            continue;
+        }
+        info!("found content pack {}", pack.name);
+        packs.packs.push(pack);
+    }
+}
+
+fn load_pack_file<A: Asset>(
+    assets: &AssetServer,
+    dir: &Path,
+    pack: &str,
+    file: &str,
+) -> Option<Handle<A>> {
+    dir.join(pack)
+        .join(file)
+        .is_file()
+        .then(|| assets.load(format!("{PACKS_SOURCE}://{pack}/{file}")))
+}
+
+/// Adds the parts of loaded packs to the library, again after it is reloaded. A part whose
+/// name is taken by the game or an earlier pack is left out.
+fn merge_pack_parts(
+    mut packs: ResMut<ContentPacks>,
+    pack_parts: Res<Assets<MachinePartConfigByType>>,
+    mut library: ResMut<MachinePartConfigByType>,
+) {
+    let packs = &mut *packs;
+    for pack in &packs.packs {
+        let Some(parts) = pack
+            .parts
+            .as_ref()
+            .and_then(|handle| pack_parts.get(handle))
+        else {
+            continue;
+        };
+        for (part, config) in &parts.0 {
+            let owner = packs.merged.get(part);
+            if !library.0.contains_key(part) {
+                library.0.insert(part.clone(), config.clone());
+                packs.merged.insert(part.clone(), pack.name.clone());
+            } else if owner != Some(&pack.name)
+                && packs.reported.insert((pack.name.clone(), part.clone()))
+            {
+                let taken_by = owner.map_or("the game", String::as_str);
+                error!(
+                    "content pack {}: part \"{part}\" is already defined by {taken_by}, skipped",
+                    pack.name
+                );
+            }
+        }
+    }
+}
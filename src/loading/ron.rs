@@ -52,9 +52,9 @@ pub enum RonLoaderError {
     /// An [IO Error](std::io::Error)
     #[error("Could not read the file: {0}")]
     Io(#[from] std::io::Error),
-    /// A [RON Error](ron::error::SpannedError)
-    #[error("Could not parse RON: {0}")]
-    RonError(#[from] ron::error::SpannedError),
+    /// A [RON Error](ron::error::SpannedError) pointed at the file and line
+    #[error(transparent)]
+    RonError(#[from] super::validation::ContentIssue),
 }
 
 impl<A> AssetLoader for RonAssetLoader<A>
@@ -69,11 +69,13 @@ where
         &self,
         reader: &mut dyn Reader,
         _settings: &(),
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let asset = ron::de::from_bytes::<A>(&bytes)?;
+        let asset = ron::de::from_bytes::<A>(&bytes).map_err(|e| {
+            super::validation::ContentIssue::from_ron(&load_context.path().to_string_lossy(), &e)
+        })?;
         Ok(asset)
     }
 
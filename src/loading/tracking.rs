@@ -1,7 +1,8 @@
 //! A high-level way to load collections of asset handles as resources.
 
 use super::*;
-use std::{collections::VecDeque, path::Path};
+use bevy::asset::{AssetLoadError, LoadState, RecursiveDependencyLoadState, io::AssetReaderError};
+use std::{collections::VecDeque, path::Path, sync::Arc};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<ResourceHandles>();
@@ -77,29 +78,108 @@ impl LoadResource for App {
 /// A function that inserts a loaded resource.
 type InsertLoadedResource = fn(&mut World, &UntypedHandle);
 
+/// A resource whose asset is still loading.
+pub struct PendingResource {
+    /// Short type name of the resource, shown on the loading screen.
+    pub name: &'static str,
+    handle: UntypedHandle,
+    insert: InsertLoadedResource,
+    /// The error of a failure being retried. Reloading is async, so the asset keeps this
+    /// error until the reload reports back, and it isn't a new failure.
+    retried: Option<Arc<AssetLoadError>>,
+}
+
+/// A resource that can't be inserted because its asset, or one of its dependencies, failed.
+pub struct LoadFailure {
+    pub name: &'static str,
+    /// The asset that failed, if the error names one.
+    pub path: Option<String>,
+    /// What the loader reported.
+    pub error: String,
+    cause: Arc<AssetLoadError>,
+    pending: PendingResource,
+}
+
 #[derive(Resource, Default)]
 pub struct ResourceHandles {
     // Use a queue for waiting assets so they can be cycled through and moved to
     // `finished` one at a time.
-    pub waiting: VecDeque<(UntypedHandle, InsertLoadedResource)>,
+    pub waiting: VecDeque<PendingResource>,
     finished: Vec<UntypedHandle>,
+    failed: Vec<LoadFailure>,
 }
 
 impl ResourceHandles {
     /// Returns true if all requested [`Asset`]s have finished loading and are available as [`Resource`]s.
     pub fn is_all_done(&self) -> bool {
-        self.waiting.is_empty()
+        self.waiting.is_empty() && self.failed.is_empty()
+    }
+
+    /// Fraction of the requested resources that are inserted, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let total = self.waiting.len() + self.finished.len() + self.failed.len();
+        if total == 0 {
+            return 1.0;
+        }
+        self.finished.len() as f32 / total as f32
+    }
+
+    pub fn failures(&self) -> &[LoadFailure] {
+        &self.failed
+    }
+
+    /// Reloads the assets that failed and waits for them again.
+    pub fn retry_failed(&mut self, assets: &AssetServer) {
+        for mut failure in self.failed.drain(..) {
+            if let Some(path) = &failure.path {
+                assets.reload(path.clone());
+            }
+            // a failed dependency leaves the resource's own asset failed, so it's reloaded too
+            if let Some(root) = failure.pending.handle.path() {
+                if failure.path != Some(root.to_string()) {
+                    assets.reload(root.clone());
+                }
+            }
+            failure.pending.retried = Some(failure.cause);
+            self.waiting.push_back(failure.pending);
+        }
     }
 
     /// Adds an asset handle to the list of pending assets to be tracked and converted to resources
     /// on load.
     pub fn push_handle<T: Asset + Resource + Clone>(&mut self, handle: Handle<T>) {
-        self.waiting.push_back((handle.untyped(), |world, handle| {
-            let assets = world.resource::<Assets<T>>();
-            if let Some(value) = assets.get(handle.id().typed::<T>()) {
-                world.insert_resource(value.clone());
-            }
-        }));
+        self.waiting.push_back(PendingResource {
+            name: T::short_type_path(),
+            handle: handle.untyped(),
+            insert: |world, handle| {
+                let assets = world.resource::<Assets<T>>();
+                if let Some(value) = assets.get(handle.id().typed::<T>()) {
+                    world.insert_resource(value.clone());
+                }
+            },
+            retried: None,
+        });
+    }
+}
+
+/// The error that keeps `handle` from loading, if it or a dependency failed.
+fn load_error(assets: &AssetServer, handle: &UntypedHandle) -> Option<Arc<AssetLoadError>> {
+    match assets.get_load_states(handle.id())? {
+        (LoadState::Failed(error), _, _) | (_, _, RecursiveDependencyLoadState::Failed(error)) => {
+            Some(error)
+        }
+        _ => None,
+    }
+}
+
+/// The asset `error` is about, if it names one.
+fn failed_path(error: &AssetLoadError) -> Option<String> {
+    match error {
+        AssetLoadError::AssetLoaderError(e) => Some(e.path().to_string()),
+        AssetLoadError::AssetReaderError(AssetReaderError::NotFound(path)) => {
+            Some(path.to_string_lossy().into_owned())
+        }
+        _ => None,
     }
 }
 
@@ -107,12 +187,28 @@ fn load_resource_assets(world: &mut World) {
     world.resource_scope(|world, mut resource_handles: Mut<ResourceHandles>| {
         world.resource_scope(|world, assets: Mut<AssetServer>| {
             for _ in 0..resource_handles.waiting.len() {
-                let (handle, insert_fn) = resource_handles.waiting.pop_front().unwrap();
-                if assets.is_loaded_with_dependencies(&handle) {
-                    insert_fn(world, &handle);
-                    resource_handles.finished.push(handle);
+                let mut pending = resource_handles.waiting.pop_front().unwrap();
+                let error = load_error(&assets, &pending.handle).filter(|error| {
+                    !pending
+                        .retried
+                        .as_ref()
+                        .is_some_and(|old| Arc::ptr_eq(old, error))
+                });
+                if assets.is_loaded_with_dependencies(&pending.handle) {
+                    (pending.insert)(world, &pending.handle);
+                    resource_handles.finished.push(pending.handle);
+                } else if let Some(cause) = error {
+                    error!("failed to load {}: {cause}", pending.name);
+                    pending.retried = None;
+                    resource_handles.failed.push(LoadFailure {
+                        name: pending.name,
+                        path: failed_path(&cause),
+                        error: cause.to_string(),
+                        cause,
+                        pending,
+                    });
                 } else {
-                    resource_handles.waiting.push_back((handle, insert_fn));
+                    resource_handles.waiting.push_back(pending);
                 }
             }
         });
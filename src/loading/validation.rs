@@ -0,0 +1,722 @@
+//! Checks the RON content files for mistakes the loaders would otherwise hide or panic on.
+//!
+//! Native dev builds run the shallow pass on startup and log what it finds. The deep pass also
+//! generates colliders and is what `check-content` runs.
+
+use crate::{
+    game::tutorial::{Advance, TutorialStep},
+    localization::{DEFAULT_LANGUAGE, Locales},
+    prelude::*,
+    util::image_geometry::{RgbaBuffer, rotation_colliders},
+};
+use bevy::{asset::RenderAssetUsages, prelude::*};
+use std::{fmt, path::Path};
+
+/// Release builds read nothing they don't load, `check-content` covers them.
+#[cfg(all(feature = "dev_native", not(target_arch = "wasm32")))]
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Startup, validate_on_startup);
+}
+
+/// A problem in a content file, pointing at the line to fix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentIssue {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for ContentIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: {}", self.file, self.message)?,
+            None => write!(f, "{}: {}", self.file, self.message)?,
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\n    hint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ContentIssue {}
+
+impl ContentIssue {
+    fn new(file: &str, line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            file: file.to_string(),
+            line,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Turns a RON parse error into an issue with a hint for the common mistakes.
+    pub fn from_ron(file: &str, error: &ron::error::SpannedError) -> Self {
+        use ron::Error;
+
+        let hint = match &error.code {
+            Error::NoSuchStructField {
+                expected, found, ..
+            }
+            | Error::NoSuchEnumVariant {
+                expected, found, ..
+            } => Some(match closest(found, expected.iter().copied()) {
+                Some(guess) => format!("did you mean `{guess}`?"),
+                None => format!("expected one of: {}", expected.join(", ")),
+            }),
+            Error::MissingStructField { field, .. } => Some(format!("add `{field}: ...`")),
+            Error::DuplicateStructField { field, .. } => {
+                Some(format!("`{field}` is set twice, remove one"))
+            }
+            Error::ExpectedComma
+            | Error::ExpectedArrayEnd
+            | Error::ExpectedMapEnd
+            | Error::ExpectedStructLikeEnd => {
+                Some("check for a missing comma or bracket on this or the previous line".into())
+            }
+            Error::ExpectedOption => Some("wrap the value in `Some(...)`".into()),
+            Error::Eof => Some("the file ends early, a closing bracket is probably missing".into()),
+            _ => None,
+        };
+
+        Self {
+            file: file.to_string(),
+            line: Some(error.position.line),
+            message: error.code.to_string(),
+            hint,
+        }
+    }
+}
+
+impl ContentIssue {
+    /// Points a `machine_parts.ron` error at the part it is about.
+    pub fn from_parts_error(file: &str, source: &str, error: &MachinePartsParseError) -> Self {
+        let part = match error {
+            MachinePartsParseError::RonSpannedError(e) => return Self::from_ron(file, e),
+            MachinePartsParseError::UnknownParent { part, .. }
+            | MachinePartsParseError::MissingField { part, .. } => Some(part),
+            MachinePartsParseError::DuplicatePart(part) => Some(part),
+            _ => None,
+        };
+        let line = part.and_then(|part| {
+            let needle = format!("\"{part}\":");
+            let first = line_of(source, &needle, 1)?;
+            match error {
+                // point at the second definition, the first one is the one to keep
+                MachinePartsParseError::DuplicatePart(_) => line_of(source, &needle, first + 1),
+                _ => Some(first),
+            }
+        });
+        let issue = Self::new(file, line, error.to_string());
+        match error {
+            MachinePartsParseError::UnknownParent { parent, .. } => {
+                let names = source
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix('"')?.split_once("\":"))
+                    .map(|(name, _)| name);
+                match closest(parent, names) {
+                    Some(guess) => issue.with_hint(format!("did you mean \"{guess}\"?")),
+                    None => issue,
+                }
+            }
+            MachinePartsParseError::InheritanceCycle(_) => {
+                issue.with_hint("a part can not end up extending itself")
+            }
+            MachinePartsParseError::DuplicatePart(_) => {
+                issue.with_hint("rename one of them, or remove the copy")
+            }
+            _ => issue,
+        }
+    }
+
+    /// Reports a tea sensor whose collider image splits into several shapes.
+    pub fn sensor_shapes(
+        source: &str,
+        name: &str,
+        path: &str,
+        rotation: usize,
+        shapes: usize,
+    ) -> Self {
+        let part_line = line_of(source, &format!("\"{name}\":"), 1).unwrap_or(1);
+        let message =
+            format!("{name}: TeaSensor {path} has {shapes} shapes in rotation {rotation}, not 1");
+        Self::new(
+            "machine_parts.ron",
+            line_of(source, &format!("\"{path}\""), part_line),
+            message,
+        )
+        .with_hint(
+            "a sensor needs one solid region, join the pieces in the image \
+            or set `collider_settings: (single_convex_hull: true)`",
+        )
+    }
+}
+
+/// Validates `config.ron`, `machine_parts.ron`, `levels.ron`, every level it lists,
+/// `locales.ron` and `tutorial.ron`.
+///
+/// `deep` also generates colliders, which is slow but catches sensors with more than one shape.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn validate_content(asset_root: &Path, deep: bool) -> Vec<ContentIssue> {
+    let mut issues = Vec::new();
+
+    if let Some(source) = read_source(asset_root, "config.ron", &mut issues) {
+        if let Err(e) = ron::de::from_str::<Config>(&source) {
+            issues.push(ContentIssue::from_ron("config.ron", &e));
+        }
+    }
+
+    let parts = read_source(asset_root, "machine_parts.ron", &mut issues)
+        .and_then(|source| validate_machine_parts(asset_root, &source, deep, &mut issues));
+
+    let Some(source) = read_source(asset_root, "levels.ron", &mut issues) else {
+        return issues;
+    };
+    let level_paths = match ron::de::from_str::<Vec<String>>(&source) {
+        Ok(paths) => paths,
+        Err(e) => {
+            issues.push(ContentIssue::from_ron("levels.ron", &e));
+            return issues;
+        }
+    };
+    let mut level_names = vec![];
+    for path in level_paths {
+        if !asset_root.join(&path).exists() {
+            issues.push(
+                ContentIssue::new(
+                    "levels.ron",
+                    line_of(&source, &path, 1),
+                    "missing level file",
+                )
+                .with_hint(format!("{path} does not exist under the assets folder")),
+            );
+            continue;
+        }
+        if let Some(level_source) = read_source(asset_root, &path, &mut issues) {
+            level_names.extend(validate_level(
+                &path,
+                &level_source,
+                parts.as_ref(),
+                &mut issues,
+            ));
+        }
+    }
+
+    let locales = read_source(asset_root, "locales.ron", &mut issues)
+        .and_then(|source| validate_locales(asset_root, &source, &mut issues));
+
+    if let Some(source) = read_source(asset_root, "tutorial.ron", &mut issues) {
+        let content = TutorialContent {
+            library: parts.as_ref(),
+            level_names: &level_names,
+            locales: locales.as_ref(),
+        };
+        validate_tutorial(asset_root, &source, &content, &mut issues);
+    }
+
+    issues
+}
+
+#[cfg(all(feature = "dev_native", not(target_arch = "wasm32")))]
+fn validate_on_startup() {
+    let root = bevy::asset::io::file::FileAssetReader::get_base_path().join("assets");
+    for issue in validate_content(&root, false) {
+        error!("{issue}");
+    }
+}
+
+fn read_source(asset_root: &Path, file: &str, issues: &mut Vec<ContentIssue>) -> Option<String> {
+    match std::fs::read_to_string(asset_root.join(file)) {
+        Ok(source) => Some(source),
+        Err(e) => {
+            issues.push(ContentIssue::new(
+                file,
+                None,
+                format!("could not read: {e}"),
+            ));
+            None
+        }
+    }
+}
+
+fn validate_machine_parts(
+    asset_root: &Path,
+    source: &str,
+    deep: bool,
+    issues: &mut Vec<ContentIssue>,
+) -> Option<MachinePartConfigByType> {
+    const FILE: &str = "machine_parts.ron";
+
+    let library = match parse_machine_parts(source.as_bytes()) {
+        Ok(library) => library,
+        Err(e) => {
+            issues.push(ContentIssue::from_parts_error(FILE, source, &e));
+            return None;
+        }
+    };
+
+    let mut names: Vec<_> = library.0.keys().collect();
+    names.sort();
+    for name in names {
+        let config = &library.0[name];
+        let part_line = line_of(source, &format!("\"{name}\":"), 1).unwrap_or(1);
+        let rotations = config.texture_info.rotations;
+        let frames = config.texture_info.frames.frames();
+
+        if rotations == 0 {
+            issues.push(
+                ContentIssue::new(FILE, Some(part_line), format!("{name}: rotations is 0"))
+                    .with_hint("a part needs at least one rotation row, set `rotations: 1`"),
+            );
+            continue;
+        }
+
+        // (path, rows are rotations, columns are frames)
+        let mut images = vec![];
+        if !config.icon.path.is_empty() {
+            images.push((config.icon.path.as_str(), false, false));
+        }
+        for subassembly in &config.subassemblies {
+            match subassembly {
+                SubAssembly::Sprite {
+                    sprite_asset_path, ..
+                } => images.push((sprite_asset_path.as_str(), true, true)),
+                SubAssembly::ParticleVessel { texture_path, .. } => {
+                    images.push((texture_path.as_str(), false, false))
+                }
+                SubAssembly::TeaParticleVessel {
+                    texture_path,
+                    particle_texture_path,
+                    ..
+                } => {
+                    images.push((texture_path.as_str(), false, false));
+                    images.push((particle_texture_path.as_str(), false, false));
+                }
+                SubAssembly::FlowField {
+                    flow_texture_path, ..
+                } => images.push((flow_texture_path.as_str(), true, false)),
+                SubAssembly::TeaSensor {
+                    icon_asset_path, ..
+                } => images.push((icon_asset_path.as_str(), false, false)),
+                _ => {}
+            }
+            if let Some((path, _)) = subassembly.collider_source() {
+                images.push((path, true, false));
+            }
+        }
+
+        for subassembly in &config.subassemblies {
+            let SubAssembly::Sound {
+                ambient_path,
+                one_shot_path,
+                ..
+            } = subassembly
+            else {
+                continue;
+            };
+            for path in [ambient_path, one_shot_path].into_iter().flatten() {
+                if !asset_root.join(path).exists() {
+                    let line = line_of(source, &format!("\"{path}\""), part_line);
+                    issues.push(
+                        ContentIssue::new(FILE, line, format!("{name}: missing sound {path}"))
+                            .with_hint("paths are relative to the assets folder"),
+                    );
+                }
+            }
+        }
+
+        for (path, rows, columns) in images {
+            let line = line_of(source, &format!("\"{path}\""), part_line);
+            let Ok((width, height)) = image::image_dimensions(asset_root.join(path)) else {
+                issues.push(
+                    ContentIssue::new(FILE, line, format!("{name}: missing texture {path}"))
+                        .with_hint("paths are relative to the assets folder"),
+                );
+                continue;
+            };
+            if rows && height % rotations != 0 {
+                let message = format!(
+                    "{name}: {path} is {height}px tall, not a multiple of {rotations} rotations"
+                );
+                issues.push(
+                    ContentIssue::new(FILE, line, message)
+                        .with_hint("each rotation is one row, fix `rotations` or the image"),
+                );
+            }
+            if columns && width % frames != 0 {
+                let message =
+                    format!("{name}: {path} is {width}px wide, not a multiple of {frames} frames");
+                issues.push(
+                    ContentIssue::new(FILE, line, message)
+                        .with_hint("each animation frame is one column of the sheet"),
+                );
+            }
+        }
+
+        if deep {
+            validate_sensor_colliders(asset_root, source, name, config, issues);
+        }
+    }
+
+    Some(library)
+}
+
+fn validate_sensor_colliders(
+    asset_root: &Path,
+    source: &str,
+    name: &str,
+    config: &MachinePartConfig,
+    issues: &mut Vec<ContentIssue>,
+) {
+    for subassembly in &config.subassemblies {
+        let SubAssembly::TeaSensor { .. } = subassembly else {
+            continue;
+        };
+        let Some((path, settings)) = subassembly.collider_source() else {
+            continue;
+        };
+        let Ok(decoded) = image::open(asset_root.join(path)) else {
+            // already reported as a missing texture
+            continue;
+        };
+        let image = Image::from_dynamic(decoded, true, RenderAssetUsages::default());
+        let image = RgbaBuffer::from_image(&image);
+        let colliders = rotation_colliders(&image, config.texture_info.rotations, settings);
+        for (rotation, row) in colliders.iter().enumerate() {
+            if row.len() != 1 {
+                let issue = ContentIssue::sensor_shapes(source, name, path, rotation, row.len());
+                issues.push(issue);
+            }
+        }
+    }
+}
+
+/// Returns the level's name if it parsed.
+fn validate_level(
+    file: &str,
+    source: &str,
+    library: Option<&MachinePartConfigByType>,
+    issues: &mut Vec<ContentIssue>,
+) -> Option<String> {
+    let level = match ron::de::from_str::<LevelConfig>(source) {
+        Ok(level) => level,
+        Err(e) => {
+            issues.push(ContentIssue::from_ron(file, &e));
+            return None;
+        }
+    };
+    let Some(library) = library else {
+        return Some(level.name);
+    };
+
+    let mut search_from = 1;
+    let available = level.available_machine_parts.iter();
+    let initial = level.initial_machine_parts.iter().map(|part| &part.name);
+    for name in available.chain(initial) {
+        let line = line_of(source, &format!("\"{name}\""), search_from);
+        search_from = line.unwrap_or(search_from);
+        if !library.0.contains_key(name) {
+            let mut issue =
+                ContentIssue::new(file, line, format!("unknown machine part \"{name}\""));
+            if let Some(guess) = closest(name, library.0.keys().map(String::as_str)) {
+                issue = issue.with_hint(format!("did you mean \"{guess}\"?"));
+            } else {
+                issue = issue.with_hint("part names are the keys of machine_parts.ron");
+            }
+            issues.push(issue);
+        }
+    }
+
+    for part in &level.initial_machine_parts {
+        let Some(config) = library.0.get(&part.name) else {
+            continue;
+        };
+        if part.context.rotation_index >= config.texture_info.rotations {
+            issues.push(ContentIssue::new(
+                file,
+                line_of(source, &format!("\"{}\"", part.name), 1),
+                format!(
+                    "\"{}\" has rotation_index {} but only {} rotations",
+                    part.name, part.context.rotation_index, config.texture_info.rotations
+                ),
+            ));
+        }
+    }
+
+    Some(level.name)
+}
+
+fn validate_locales(
+    asset_root: &Path,
+    source: &str,
+    issues: &mut Vec<ContentIssue>,
+) -> Option<Locales> {
+    const FILE: &str = "locales.ron";
+
+    let locales = match ron::de::from_str::<Locales>(source) {
+        Ok(locales) => locales,
+        Err(e) => {
+            issues.push(ContentIssue::from_ron(FILE, &e));
+            return None;
+        }
+    };
+    if !locales.0.contains_key(DEFAULT_LANGUAGE) {
+        issues.push(
+            ContentIssue::new(FILE, None, format!("no \"{DEFAULT_LANGUAGE}\" table"))
+                .with_hint("every other language falls back to it"),
+        );
+    }
+    for font in locales.0.values().filter_map(|locale| locale.font.as_ref()) {
+        if !asset_root.join(font).exists() {
+            issues.push(
+                ContentIssue::new(FILE, line_of(source, font, 1), "missing font")
+                    .with_hint(format!("{font} does not exist under the assets folder")),
+            );
+        }
+    }
+    Some(locales)
+}
+
+/// What tutorial steps refer to.
+struct TutorialContent<'a> {
+    library: Option<&'a MachinePartConfigByType>,
+    level_names: &'a [String],
+    locales: Option<&'a Locales>,
+}
+
+fn validate_tutorial(
+    asset_root: &Path,
+    source: &str,
+    content: &TutorialContent,
+    issues: &mut Vec<ContentIssue>,
+) {
+    const FILE: &str = "tutorial.ron";
+    let TutorialContent {
+        library,
+        level_names,
+        locales,
+    } = *content;
+
+    let steps = match ron::de::from_str::<Vec<TutorialStep>>(source) {
+        Ok(steps) => steps,
+        Err(e) => {
+            issues.push(ContentIssue::from_ron(FILE, &e));
+            return;
+        }
+    };
+
+    let english = locales.and_then(|locales| locales.0.get(DEFAULT_LANGUAGE));
+    for step in &steps {
+        // plain text is fine, but something that looks like a key should be in the table
+        if let Some(english) = english {
+            if step.text.starts_with("tutorial.") && !english.strings.contains_key(&step.text) {
+                let line = line_of(source, &format!("\"{}\"", step.text), 1);
+                issues.push(
+                    ContentIssue::new(FILE, line, format!("unknown text key \"{}\"", step.text))
+                        .with_hint(format!("add it to the \"{DEFAULT_LANGUAGE}\" table")),
+                );
+            }
+        }
+        if let Some(image) = &step.image {
+            if !asset_root.join(image).exists() {
+                issues.push(
+                    ContentIssue::new(FILE, line_of(source, image, 1), "missing tutorial image")
+                        .with_hint(format!("{image} does not exist under the assets folder")),
+                );
+            }
+        }
+        if let Some(level) = &step.level {
+            if !level_names.contains(level) {
+                let line = line_of(source, &format!("\"{level}\""), 1);
+                let mut issue = ContentIssue::new(FILE, line, format!("unknown level \"{level}\""));
+                if let Some(guess) = closest(level, level_names.iter().map(String::as_str)) {
+                    issue = issue.with_hint(format!("did you mean \"{guess}\"?"));
+                } else {
+                    issue = issue.with_hint("levels are bound by the `name` in their file");
+                }
+                issues.push(issue);
+            }
+        }
+        if let (Advance::PlacePart(name), Some(library)) = (&step.advance, library) {
+            if !library.0.contains_key(name) {
+                let line = line_of(source, &format!("PlacePart(\"{name}\")"), 1);
+                let mut issue =
+                    ContentIssue::new(FILE, line, format!("unknown machine part \"{name}\""));
+                if let Some(guess) = closest(name, library.0.keys().map(String::as_str)) {
+                    issue = issue.with_hint(format!("did you mean \"{guess}\"?"));
+                }
+                issues.push(issue);
+            }
+        }
+    }
+}
+
+/// 1-based line of the first occurrence of `needle` at or after line `from`.
+fn line_of(source: &str, needle: &str, from: usize) -> Option<usize> {
+    source
+        .lines()
+        .enumerate()
+        .skip(from.saturating_sub(1))
+        .find(|(_, line)| line.contains(needle))
+        .map(|(i, _)| i + 1)
+}
+
+/// The candidate with the smallest edit distance, if it is close enough to be a typo.
+fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 3.max(word.len() / 3))
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1)
+                .min(row[j] + 1)
+                .min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// An assets folder of the test's own under the temp dir, with `images` in it.
+    fn asset_root(test: &str, images: &[(&str, image::RgbaImage)]) -> PathBuf {
+        let folder = format!("chai-reaction-{test}-{}", std::process::id());
+        let root = std::env::temp_dir().join(folder);
+        for (path, pixels) in images {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            pixels.save(path).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn line_of_counts_from_one_and_skips_earlier_lines() {
+        let source = "(\n    \"Fan\": (),\n    \"Pipe\": (),\n    \"Fan\": (),\n)";
+        assert_eq!(line_of(source, "\"Fan\"", 1), Some(2));
+        assert_eq!(line_of(source, "\"Fan\"", 3), Some(4));
+        assert_eq!(line_of(source, "\"Fan\"", 5), None);
+        assert_eq!(line_of(source, "\"Shelf\"", 1), None);
+    }
+
+    #[test]
+    fn closest_only_suggests_typos() {
+        let names = ["Wood Ramp", "Wood Block", "Fan"];
+        assert_eq!(closest("Wood Rmap", names.into_iter()), Some("Wood Ramp"));
+        assert_eq!(closest("wood block", names.into_iter()), Some("Wood Block"));
+        assert_eq!(closest("Teapot", names.into_iter()), None);
+
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "Fan"), 3);
+        assert_eq!(edit_distance("Fan", "Fan"), 0);
+    }
+
+    #[test]
+    fn unknown_parts_in_a_level_are_reported_with_a_guess() {
+        let library =
+            parse_machine_parts(br#"({ "Wood Ramp": (cost: 1, is_dynamic: false) })"#).unwrap();
+        let source = r#"(
+    name: "Test",
+    zen_points: 10,
+    available_machine_parts: [
+        "Wood Ramp",
+        "Wood Rmap",
+    ],
+    initial_machine_parts: [],
+)"#;
+        let mut issues = vec![];
+        let name = validate_level("levels/test.ron", source, Some(&library), &mut issues);
+        assert_eq!(name.as_deref(), Some("Test"));
+        assert_eq!(
+            issues,
+            [ContentIssue::new(
+                "levels/test.ron",
+                Some(6),
+                "unknown machine part \"Wood Rmap\""
+            )
+            .with_hint("did you mean \"Wood Ramp\"?")]
+        );
+    }
+
+    #[test]
+    fn rotations_have_to_divide_the_image() {
+        let root = asset_root(
+            "rotations",
+            &[("textures/block.png", image::RgbaImage::new(16, 10))],
+        );
+        let source = r#"({
+    "Block": (
+        cost: 1,
+        is_dynamic: false,
+        texture_info: (rotations: 3),
+        subassemblies: [
+            Collider(mesh_image_path: "textures/block.png"),
+        ],
+    ),
+})"#;
+        let mut issues = vec![];
+        assert!(validate_machine_parts(&root, source, false, &mut issues).is_some());
+        let message = "Block: textures/block.png is 10px tall, not a multiple of 3 rotations";
+        assert_eq!(
+            issues,
+            [ContentIssue::new("machine_parts.ron", Some(7), message)
+                .with_hint("each rotation is one row, fix `rotations` or the image")]
+        );
+    }
+
+    #[test]
+    fn tea_sensors_with_several_shapes_are_reported_by_the_deep_pass() {
+        let islands = image::RgbaImage::from_fn(16, 8, |x, y| {
+            let solid = ((1..6).contains(&x) || (10..15).contains(&x)) && (1..7).contains(&y);
+            image::Rgba(if solid { [255; 4] } else { [0; 4] })
+        });
+        let root = asset_root("sensor", &[("textures/cup.png", islands)]);
+        let source = r#"({
+    "Cup": (
+        cost: 1,
+        is_dynamic: false,
+        subassemblies: [
+            TeaSensor(
+                mesh_image_path: "textures/cup.png",
+                name: "Black Tea",
+                icon_asset_path: "textures/cup.png",
+            ),
+        ],
+    ),
+})"#;
+        let mut issues = vec![];
+        validate_machine_parts(&root, source, false, &mut issues);
+        assert!(issues.is_empty(), "{issues:?}");
+
+        validate_machine_parts(&root, source, true, &mut issues);
+        let [issue] = &issues[..] else {
+            panic!("expected one issue, got {issues:?}");
+        };
+        assert_eq!(issue.line, Some(7));
+        assert_eq!(
+            issue.message,
+            "Cup: TeaSensor textures/cup.png has 2 shapes in rotation 0, not 1"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! String tables per language from `locales.ron`.
+//!
+//! Text is looked up in the language picked in the settings, then in English, and shown as
+//! the key itself when neither has it. Plain English text is its own key, so every [`label`]
+//! and button is translated without changes, while ids like a part's name stay stable and
+//! only their display key is looked up. Text with values in it is a whole sentence with
+//! `{placeholders}`, filled in after it's translated.
+
+use crate::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Language every other one falls back to.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+pub fn plugin(app: &mut App) {
+    app.add_plugins(RonAssetPlugin::<Locales>::new(&["locales.ron"]))
+        .load_resource_from_path::<Locales>("locales.ron")
+        .add_systems(Update, localize_texts.run_if(resource_exists::<Locales>));
+}
+
+/// Languages by their code, like `"en"`.
+#[derive(Asset, Resource, Reflect, Clone, Debug, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct Locales(pub BTreeMap<String, Locale>);
+
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct Locale {
+    /// Shown in the language setting.
+    pub name: String,
+    /// Asset path of a font with this language's glyphs, the default font only has ASCII.
+    #[serde(default)]
+    pub font: Option<String>,
+    pub strings: BTreeMap<String, String>,
+}
+
+impl Locales {
+    pub fn get<'a>(&'a self, language: &str, key: &'a str) -> &'a str {
+        [language, DEFAULT_LANGUAGE]
+            .into_iter()
+            .filter_map(|code| self.0.get(code))
+            .find_map(|locale| locale.strings.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// The language after `language`, wrapping around.
+    pub fn next_language(&self, language: &str) -> String {
+        let codes: Vec<_> = self.0.keys().collect();
+        let next = codes
+            .iter()
+            .position(|code| *code == language)
+            .map_or(0, |index| (index + 1) % codes.len());
+        codes
+            .get(next)
+            .map_or_else(|| DEFAULT_LANGUAGE.to_string(), |code| code.to_string())
+    }
+}
+
+/// Translates text in the language picked in the [`Settings`].
+#[derive(SystemParam)]
+pub struct Localization<'w> {
+    locales: Option<Res<'w, Locales>>,
+    settings: Res<'w, Settings>,
+}
+
+impl Localization<'_> {
+    pub fn get(&self, key: &str) -> String {
+        match &self.locales {
+            Some(locales) => locales.get(&self.settings.language, key).to_string(),
+            None => key.to_string(),
+        }
+    }
+
+    /// Translates `key` and fills in its `{placeholders}`, so a translation can put the
+    /// values wherever its grammar wants them.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        args.iter().fold(self.get(key), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+    }
+
+    /// Whether the language or the string tables changed since the system last ran.
+    pub fn is_changed(&self) -> bool {
+        self.settings.is_changed() || self.locales.as_ref().is_some_and(|l| l.is_changed())
+    }
+
+    /// Name of the current language in that language.
+    pub fn language_name(&self) -> String {
+        let locale = self
+            .locales
+            .as_ref()
+            .and_then(|l| l.0.get(&self.settings.language));
+        locale.map_or_else(|| self.settings.language.clone(), |l| l.name.clone())
+    }
+}
+
+/// Keeps the [`Text`] on this entity in the current language. Texts that something else
+/// rewrites after spawning, like counters, are left to it and only get the language's font.
+#[derive(Component, Clone, Debug)]
+pub struct LocalizedText {
+    key: String,
+    shown: Option<String>,
+}
+
+impl LocalizedText {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            shown: None,
+        }
+    }
+}
+
+fn localize_texts(
+    assets: Res<AssetServer>,
+    locales: Res<Locales>,
+    settings: Res<Settings>,
+    mut language: Local<Option<String>>,
+    mut font: Local<Handle<Font>>,
+    mut texts: Query<(&mut Text, &mut TextFont, &mut LocalizedText)>,
+) {
+    let refresh = locales.is_changed() || language.as_ref() != Some(&settings.language);
+    if refresh {
+        *language = Some(settings.language.clone());
+        *font = locales
+            .0
+            .get(&settings.language)
+            .and_then(|locale| locale.font.as_ref())
+            .map(|path| assets.load(path))
+            .unwrap_or_default();
+    }
+
+    for (mut text, mut text_font, mut localized) in &mut texts {
+        if !refresh && !localized.is_added() {
+            continue;
+        }
+        if text_font.font != *font {
+            text_font.font = font.clone();
+        }
+        // empty texts are placeholders for something else to fill
+        if localized.key.is_empty()
+            || localized
+                .shown
+                .as_ref()
+                .is_some_and(|shown| *shown != text.0)
+        {
+            continue;
+        }
+        let translated = locales.get(&settings.language, &localized.key).to_string();
+        text.set_if_neq(Text(translated.clone()));
+        localized.shown = Some(translated);
+    }
+}
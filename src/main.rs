@@ -9,11 +9,15 @@ use std::io::Cursor;
 use winit::window::Icon;
 
 mod audio;
+mod cli;
 mod dev_tools;
 mod game;
 mod loading;
+mod localization;
 mod pre_load;
 mod screens;
+#[cfg(test)]
+mod tests;
 mod ui;
 mod util;
 
@@ -37,6 +41,7 @@ pub(crate) mod prelude {
         tea::*,
     };
     pub use loading::{AudioSources, ResourceHandles, Textures, textures::TexturesLoaderPlugin};
+    pub use localization::{Localization, LocalizedText};
     pub use pre_load::Config;
     pub use screens::Screen;
     pub use ui::{in_game_ui::*, *};
@@ -48,8 +53,58 @@ pub(crate) mod prelude {
 }
 
 fn main() {
+    let args = cli::Args::from_env().unwrap_or_else(|e| {
+        eprintln!("{e}\n\n{}", cli::USAGE);
+        std::process::exit(2);
+    });
+    match &args.command {
+        Some(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Some(cli::Command::BakeColliders(root)) => {
+            let root = std::path::Path::new(root);
+            match game::machine_parts::collider_cache::bake_colliders(root) {
+                Ok(count) => println!("baked colliders for {count} images"),
+                Err(e) => {
+                    eprintln!("failed to bake colliders: {e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Some(cli::Command::CheckContent(root)) => {
+            let issues = loading::validation::validate_content(std::path::Path::new(root), true);
+            for issue in &issues {
+                eprintln!("{issue}");
+            }
+            if !issues.is_empty() {
+                eprintln!("{} content issues found", issues.len());
+                std::process::exit(1);
+            }
+            println!("content ok");
+            return;
+        }
+        _ => {}
+    }
+
     let mut app = App::new();
 
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(cli::Command::Replay(path)) = &args.command {
+        match game::replay::Replay::load(std::path::Path::new(path)) {
+            Ok(replay) => {
+                app.insert_resource(game::replay::PendingReplay(replay));
+            }
+            Err(e) => {
+                eprintln!("failed to load replay: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     app.configure_sets(
         Update,
         (
@@ -66,6 +121,7 @@ fn main() {
             fit_canvas_to_parent: true,
             // Tells wasm not to override default event handling, like F5 and Ctrl+R
             prevent_default_event_handling: false,
+            mode: args.window_mode.unwrap_or_default(),
             ..default()
         }),
         ..default()
@@ -83,6 +139,8 @@ fn main() {
         ..Default::default()
     };
 
+    app.insert_resource(args);
+    loading::packs::register_source(&mut app);
     app.add_plugins(
         DefaultPlugins
             .set(ImagePlugin::default_nearest())
@@ -94,8 +152,10 @@ fn main() {
     // custom plugins. the order is important
     // be sure you use resources/types AFTER you add plugins that insert them
     app.add_plugins((
+        cli::plugin,
         audio::plugin,
         loading::plugin,
+        localization::plugin,
         ui::plugin,
         screens::plugin,
         dev_tools::plugin,
@@ -13,6 +13,19 @@ pub struct Config {
     pub screen_size_in_tiles: UVec2,
 }
 
+impl Config {
+    /// Smallest world-space area the camera shows at the default zoom.
+    pub fn view_size(&self) -> Vec2 {
+        16.0 * self.screen_size_in_tiles.as_vec2() + Vec2::new(16.0, 48.0)
+    }
+
+    /// Bounds of levels that don't declare their own: the part of a single screen that
+    /// isn't covered by the top bar.
+    pub fn screen_bounds(&self) -> Rect {
+        Rect::from_center_size(Vec2::ZERO, self.view_size() - Vec2::new(0.0, 64.0))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
 pub struct Physics {
     pub water: Water,
@@ -1,21 +1,32 @@
 //! The screen state for the main gameplay.
 
 use super::*;
-use crate::{game::input_dispatch::*, game::physics::PhysicsState, screens::settings};
+use crate::{
+    game::input_dispatch::*,
+    game::physics::{PhysicsState, SimulationSpeed},
+    game::settings::Rebinding,
+    game::tutorial::{TutorialAnchor, tutorial_modal_open},
+    screens::settings,
+};
 use bevy::ui::Val::*;
 use leafwing_input_manager::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins(crate::game::plugin)
         .add_systems(OnEnter(Screen::Gameplay), spawn_gameplay_ui)
-        .add_systems(Startup, load_checks)
         .add_systems(
             Update,
             (
-                (toggle_physics_on_space, change_score, restart_on_r, update_conditions)
-                    .run_if(in_state(Screen::Gameplay)),
+                (
+                    toggle_physics_on_space.run_if(not(tutorial_modal_open)),
+                    restart_on_r,
+                )
+                    .run_if(in_state(Screen::Gameplay))
+                    .run_if(not(resource_exists::<Rebinding>)),
+                update_speed_label.run_if(in_state(Screen::Gameplay)),
                 instant_victory
-                    .run_if(resource_exists::<LoadedLevel>.and(resource_exists::<LevelList>)),
+                    .run_if(resource_exists::<LoadedLevel>.and(resource_exists::<LevelList>))
+                    .run_if(not(resource_exists::<Rebinding>)),
             ),
         )
         .add_systems(OnExit(Screen::Gameplay), clear_level)
@@ -27,30 +38,10 @@ pub(super) fn plugin(app: &mut App) {
         .add_observer(clear_modals);
 }
 
-fn clear_level(
-    mut commands:Commands
-) {
+fn clear_level(mut commands: Commands) {
     commands.queue(ClearLevel);
 }
 
-fn load_checks(
-    mut commands: Commands,
-    assets: Res<AssetServer>,
-) {
-    let checked = assets.load("textures/icons/check_tick.png");
-    let empty = assets.load("textures/icons/check_empty.png");
-    commands.insert_resource(CheckboxImages {
-        checked,
-        empty,
-    });
-}
-
-#[derive(Resource)]
-pub struct CheckboxImages {
-    empty: Handle<Image>,
-    checked: Handle<Image>,
-}
-
 #[derive(Resource)]
 pub struct NextLevel(pub usize);
 
@@ -60,9 +51,12 @@ pub struct ScoreTimer(pub Timer);
 pub struct GameplayUi;
 #[derive(Component)]
 pub struct PauseLabel;
+/// Holds a recipe HUD row for every tea sensor, see [`crate::ui::in_game_ui::recipe_hud`].
 #[derive(Component)]
 pub struct ScoreLabel;
 #[derive(Component)]
+pub struct SpeedLabel;
+#[derive(Component)]
 pub struct MenuModal;
 #[derive(Component)]
 pub struct SettingsModal;
@@ -71,46 +65,13 @@ pub struct LevelFinishedModal;
 #[derive(Component)]
 pub struct GameFinishedModal;
 
-fn update_conditions(
-    mut commands: Commands,
-    sensors: Query<(&Name, Has<Satisfied>, &TeaSensor)>,
-    images: Res<CheckboxImages>,
-    score_area: Single<Entity, With<ScoreLabel>>,
-) {
-    commands.entity(*score_area).despawn_related::<Children>();
-    for (name, is_satisfied, sensor) in &sensors {
-        commands.entity(*score_area).with_children(|parent| {
-            parent.spawn((
-                Node {
-                    flex_direction: FlexDirection::Row,
-                    width: Percent(100.0),
-                    ..default()
-                },
-                children![
-                    ImageNode::new(if is_satisfied {
-                        images.checked.clone()
-                    } else {
-                        images.empty.clone()
-                    }),
-                    ImageNode::new(sensor.1.clone()),
-                    label(name.to_string()),
-                ],
-            ));
-        });
-    }
-
-}
-
-fn spawn_gameplay_ui(
-    mut commands: Commands,
-    textures: Res<Textures>,
-) {
-
+fn spawn_gameplay_ui(mut commands: Commands, textures: Res<Textures>) {
     let (play, exit, reset) = (
         textures.play.clone(),
         textures.exit.clone(),
         textures.reset.clone(),
     );
+    let speed_opts = Opts::default().width(Vw(3.0)).bg_color(TRANSPARENT);
     let nav_opts = Opts::default()
         .image(exit)
         .width(Vw(5.0))
@@ -136,10 +97,33 @@ fn spawn_gameplay_ui(
                 BackgroundColor(TRANSLUCENT),
                 children![
                     btn(nav_opts.clone(), to::title),
-                    btn(nav_opts.clone().image(play), click_toggle_physics),
+                    (
+                        TutorialAnchor::new("play"),
+                        btn(nav_opts.clone().image(play), click_toggle_physics),
+                    ),
                     btn(nav_opts.image(reset), init_level),
                 ]
             ),
+            (
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::SpaceEvenly,
+                    align_items: AlignItems::Center,
+                    left: Vw(42.0),
+                    top: Vw(5.0),
+                    width: Vw(16.0),
+                    ..Default::default()
+                },
+                TutorialAnchor::new("speed"),
+                BackgroundColor(TRANSLUCENT),
+                children![
+                    btn_small(speed_opts.clone().text("-"), click_slow_down),
+                    (SpeedLabel, label("")),
+                    btn_small(speed_opts.clone().text("+"), click_speed_up),
+                    btn_small(speed_opts.text(">|"), click_step_physics),
+                ]
+            ),
             (
                 Node {
                     position_type: PositionType::Absolute,
@@ -151,6 +135,7 @@ fn spawn_gameplay_ui(
                 },
                 BorderRadius::all(Px(BORDER_RADIUS)),
                 ScoreLabel,
+                TutorialAnchor::new("recipes"),
                 BackgroundColor(DIM_GREEN),
             )
         ],
@@ -159,20 +144,14 @@ fn spawn_gameplay_ui(
 
 // TODO: Gameplay UI and systems
 
-fn change_score(
-    sensors: Query<(&Name, Has<Satisfied>), With<TeaSensor>>,
-    mut score_label: Query<&mut Text, With<ScoreLabel>>,
+fn update_speed_label(
+    speed: Res<SimulationSpeed>,
+    localization: Localization,
+    mut labels: Query<&mut Text, With<SpeedLabel>>,
 ) {
-    let mut score = String::new();
-    for (n, s) in sensors.iter() {
-        if s {
-            score.push('✓');
-        }
-        score.push_str(n.as_str());
-        score.push('\n');
-    }
-    if let Ok(mut label) = score_label.single_mut() {
-        label.0 = score;
+    let text = speed.label(&localization);
+    for mut label in &mut labels {
+        label.set_if_neq(Text(text.clone()));
     }
 }
 
@@ -229,13 +208,9 @@ fn toggle_physics(
     }
 }
 
-fn toggle_physics_on_space(
-    action: Query<&ActionState<Action>>,
-    mut commands: Commands,
-    settings: Res<Settings>,
-) {
+fn toggle_physics_on_space(action: Query<&ActionState<Action>>, mut commands: Commands) {
     if let Ok(state) = action.single() {
-        if state.just_pressed(&Action::TogglePhysics) && !settings.tutorial {
+        if state.just_pressed(&Action::TogglePhysics) {
             commands.trigger(OnPhysicsToggle);
         }
     }
@@ -291,6 +266,15 @@ fn click_to_menu(_: Trigger<Pointer<Click>>, mut commands: Commands) {
 fn click_toggle_physics(_: Trigger<Pointer<Click>>, mut commands: Commands) {
     commands.trigger(OnPhysicsToggle);
 }
+fn click_slow_down(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(OnSimulationSpeedChange(-1));
+}
+fn click_speed_up(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(OnSimulationSpeedChange(1));
+}
+fn click_step_physics(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(OnPhysicsStep);
+}
 fn click_pop_modal(_: Trigger<Pointer<Click>>, mut commands: Commands) {
     commands.trigger(OnPopModal);
 }
@@ -335,8 +319,12 @@ fn add_new_modal(
     commands.trigger(OnClearModals);
     let OnNewModal(modal) = trig.event();
     match modal {
-        Modal::Main => { commands.spawn(menu_modal()); },
-        Modal::Settings => { commands.spawn(settings_modal()); },
+        Modal::Main => {
+            commands.spawn(menu_modal());
+        }
+        Modal::Settings => {
+            commands.spawn(settings_modal());
+        }
         Modal::LevelFinished => {
             commands.spawn(level_finished_modal());
         }
@@ -1,24 +1,143 @@
 //! A loading screen during which game assets are loaded.
 //! This reduces stuttering, especially for audio on WASM.
+//!
+//! It shows how much is loaded and what is still missing. When an asset fails, its path and
+//! the loader's error are listed with a button to try again.
 
 use super::*;
+use bevy::ui::Val::*;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Loading), spawn_loading_screen)
+        .add_systems(
+            Update,
+            (update_progress, update_pending_label, list_failures)
+                .run_if(in_state(Screen::Loading)),
+        )
         .add_systems(
             Update,
             continue_to_menu_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)),
         );
 }
 
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct PendingLabel;
+
+#[derive(Component)]
+struct FailureList;
+
 fn spawn_loading_screen(mut commands: Commands) {
     commands.spawn((
         StateScoped(Screen::Loading),
         ui_root("loading screen"),
-        children![label("Loading...")],
+        children![
+            label("Loading..."),
+            (
+                Name::new("Progress"),
+                Node {
+                    width: Vw(40.0),
+                    height: Px(16.0),
+                    border: UiRect::all(Px(2.0)),
+                    ..default()
+                },
+                BorderColor(WHITEISH),
+                BorderRadius::all(Px(8.0)),
+                children![(
+                    ProgressBar,
+                    Node {
+                        width: Percent(0.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(LIGHT_GREEN),
+                    BorderRadius::all(Px(6.0)),
+                )],
+            ),
+            (label(Opts::new("").font_size(16.0)), PendingLabel),
+            (
+                FailureList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Px(8.0),
+                    max_width: Vw(80.0),
+                    ..default()
+                },
+            ),
+        ],
     ));
 }
 
+fn update_progress(
+    resource_handles: Res<ResourceHandles>,
+    mut bar: Single<&mut Node, With<ProgressBar>>,
+) {
+    let width = Percent(resource_handles.progress() * 100.0);
+    if bar.width != width {
+        bar.width = width;
+    }
+}
+
+fn update_pending_label(
+    resource_handles: Res<ResourceHandles>,
+    localization: Localization,
+    mut label: Single<&mut Text, With<PendingLabel>>,
+) {
+    let pending: Vec<_> = resource_handles.waiting.iter().map(|p| p.name).collect();
+    let text = if pending.is_empty() {
+        String::new()
+    } else {
+        localization.format(
+            "Waiting for {resources}",
+            &[("resources", &pending.join(", "))],
+        )
+    };
+    label.set_if_neq(Text(text));
+}
+
+/// Rebuilds the list of failed assets whenever it changes.
+fn list_failures(
+    mut commands: Commands,
+    resource_handles: Res<ResourceHandles>,
+    list: Single<Entity, With<FailureList>>,
+    mut shown: Local<Vec<(&'static str, String)>>,
+) {
+    let failures = resource_handles.failures();
+    let current: Vec<_> = failures.iter().map(|f| (f.name, f.error.clone())).collect();
+    if current == *shown {
+        return;
+    }
+    *shown = current;
+
+    commands.entity(*list).despawn_related::<Children>();
+    if failures.is_empty() {
+        return;
+    }
+    commands.entity(*list).with_children(|parent| {
+        parent.spawn(label(Opts::new("Failed to load").color(FIRE)));
+        for failure in failures {
+            let what = match &failure.path {
+                Some(path) => format!("{}: {path}", failure.name),
+                None => failure.name.to_string(),
+            };
+            parent.spawn(label(what));
+            parent.spawn(label(Opts::new(failure.error.clone()).font_size(14.0)));
+        }
+        parent.spawn(btn("Retry", retry_loading));
+    });
+}
+
+fn retry_loading(
+    _: Trigger<OnPress>,
+    assets: Res<AssetServer>,
+    mut resource_handles: ResMut<ResourceHandles>,
+) {
+    resource_handles.retry_failed(&assets);
+}
+
 fn continue_to_menu_screen(mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
@@ -3,8 +3,13 @@
 //! For 3D, we'd also place the camera sensitivity and FOV here.
 //!
 use super::*;
-use bevy::ui::Val::*;
+use crate::{
+    game::settings::{Keybinds, Rebinding, key_name},
+    localization::Locales,
+};
+use bevy::{ecs::spawn::SpawnIter, ui::Val::*};
 use bevy_seedling::prelude::*;
+use leafwing_input_manager::prelude::*;
 
 const MIN_VOLUME: f32 = 0.0;
 const MAX_VOLUME: f32 = 3.0;
@@ -12,14 +17,23 @@ const STEP: f32 = 0.1;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<GeneralVolumeLabel>();
-    app.add_systems(OnEnter(Screen::Settings), spawn_settings_screen)
+    app.init_resource::<RebindStatus>()
+        .add_systems(OnEnter(Screen::Settings), spawn_settings_screen)
         .add_systems(
             Update,
             (
                 update_general_volume_label,
                 update_music_volume_label,
                 update_sfx_volume_label,
+                update_keybind_labels,
+                update_rebind_status_label,
+                update_language_label,
             ),
+        )
+        // after Update, so the key being bound isn't dispatched as an action this frame
+        .add_systems(
+            PostUpdate,
+            listen_for_rebind.run_if(resource_exists::<Rebinding>),
         );
 }
 
@@ -40,14 +54,168 @@ pub fn toggle_settings(
     }
 }
 
-pub fn save_settings(_: Trigger<OnPress>) {
-    info!("TODO: serialize and save settings");
+pub fn save_settings(_: Trigger<OnPress>, settings: Res<Settings>) {
+    settings.save();
+}
+
+// LANGUAGE
+fn next_language(_: Trigger<OnPress>, mut settings: ResMut<Settings>, locales: Res<Locales>) {
+    settings.language = locales.next_language(&settings.language);
+}
+
+fn update_language_label(
+    localization: Localization,
+    buttons: Query<Entity, With<LanguageButton>>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+) {
+    let name = localization.language_name();
+    for entity in &buttons {
+        for child in children.iter_descendants(entity) {
+            if let Ok(mut label) = texts.get_mut(child) {
+                label.set_if_neq(Text(name.clone()));
+            }
+        }
+    }
+}
+
+// ============================ KEYBINDINGS ============================
+
+/// What is being rebound, or why the last key was refused.
+/// Message under the keybindings, as a localization key and the values filled into it.
+/// The values are looked up too, so action names get translated and key names stay as they
+/// are.
+#[derive(Resource, Default)]
+struct RebindStatus {
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+}
+
+impl RebindStatus {
+    fn set<const N: usize>(&mut self, key: &'static str, args: [(&'static str, String); N]) {
+        self.key = key;
+        self.args = args.into();
+    }
+}
+
+/// Button showing and rebinding the keys of an action.
+#[derive(Component)]
+struct KeybindButton(Action);
+
+#[derive(Component)]
+struct RebindStatusLabel;
+
+fn start_rebind(action: Action, commands: &mut Commands, status: &mut RebindStatus) {
+    let args = [("action", action.label().to_string())];
+    status.set("Press a key for {action}, [ESC] to cancel", args);
+    commands.insert_resource(Rebinding(action));
+}
+
+fn reset_keybinds(
+    _: Trigger<OnPress>,
+    mut commands: Commands,
+    mut settings: ResMut<Settings>,
+    mut status: ResMut<RebindStatus>,
+    input_map: Single<Entity, With<InputMap<Action>>>,
+) {
+    settings.keybinds = Keybinds::default();
+    commands
+        .entity(*input_map)
+        .insert(settings.keybinds.input_map());
+    commands.remove_resource::<Rebinding>();
+    settings.save();
+    status.set("Default keys restored", []);
+}
+
+/// Binds the next key pressed to the action waiting for one, unless something else uses it.
+fn listen_for_rebind(
+    mut commands: Commands,
+    rebinding: Res<Rebinding>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut status: ResMut<RebindStatus>,
+    input_map: Single<Entity, With<InputMap<Action>>>,
+    buttons: Query<(), With<KeybindButton>>,
+) {
+    // the settings were closed while waiting
+    if buttons.is_empty() {
+        commands.remove_resource::<Rebinding>();
+        status.set("", []);
+        return;
+    }
+    let Some(key) = keys.get_just_pressed().next().copied() else {
+        return;
+    };
+    let Rebinding(action) = *rebinding;
+    if key == KeyCode::Escape && action != Action::Back {
+        commands.remove_resource::<Rebinding>();
+        status.set("Cancelled", []);
+        return;
+    }
+    if let Some(other) = settings.keybinds.conflict(action, key) {
+        let args = [("key", key_name(key)), ("action", other.to_string())];
+        status.set("{key} is already used for {action}", args);
+        return;
+    }
+
+    settings.keybinds.bind(action, key);
+    commands
+        .entity(*input_map)
+        .insert(settings.keybinds.input_map());
+    commands.remove_resource::<Rebinding>();
+    settings.save();
+    let args = [
+        ("action", action.label().to_string()),
+        ("key", key_name(key)),
+    ];
+    status.set("{action} bound to {key}", args);
+}
+
+fn update_keybind_labels(
+    settings: Res<Settings>,
+    rebinding: Option<Res<Rebinding>>,
+    buttons: Query<(Entity, &KeybindButton)>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, KeybindButton(action)) in &buttons {
+        let text = if rebinding.as_ref().is_some_and(|r| r.0 == *action) {
+            "...".to_string()
+        } else {
+            let keys = settings.keybinds.keys.get(action);
+            let names: Vec<_> = keys.into_iter().flatten().map(|k| key_name(*k)).collect();
+            if names.is_empty() {
+                "-".to_string()
+            } else {
+                names.join(", ")
+            }
+        };
+        for child in children.iter_descendants(entity) {
+            if let Ok(mut label) = texts.get_mut(child) {
+                label.set_if_neq(Text(text.clone()));
+            }
+        }
+    }
 }
 
-// TODO: implement keybinding
-// good example with serializeable keybindings:
-// <https://github.com/projectharmonia/bevy_enhanced_input/blob/master/examples/keybinding_menu.rs>
-// fn keybindings() -> impl Bundle {}
+fn update_rebind_status_label(
+    mut label: Single<&mut Text, With<RebindStatusLabel>>,
+    status: Res<RebindStatus>,
+    localization: Localization,
+) {
+    if status.is_changed() || localization.is_changed() {
+        let args: Vec<_> = status
+            .args
+            .iter()
+            .map(|(name, value)| (*name, localization.get(value)))
+            .collect();
+        let args: Vec<_> = args
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        label.0 = localization.format(status.key, &args);
+    }
+}
 
 // ============================ CONTROL KNOBS OBSERVERS ============================
 
@@ -159,7 +327,7 @@ pub fn ui() -> impl Bundle {
             BackgroundColor(TRANSLUCENT),
             header("Settings"),
             core_grid(),
-            // keybindings(),
+            keybindings(),
             navigation()
         ],
     )
@@ -173,7 +341,63 @@ fn navigation() -> impl Bundle {
             width: Percent(50.0),
             ..default()
         },
-        children![btn("Save", save_settings), btn("Back", toggle_settings),],
+        children![
+            btn("Save", save_settings),
+            btn("Default keys", reset_keybinds),
+            btn("Back", toggle_settings),
+        ],
+    )
+}
+
+fn keybindings() -> impl Bundle {
+    let rows = Action::REBINDABLE.into_iter().map(|action| {
+        (
+            Node {
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            children![
+                label(action.label()),
+                (
+                    KeybindButton(action),
+                    btn_small(
+                        "",
+                        move |_: Trigger<OnPress>,
+                              mut commands: Commands,
+                              mut status: ResMut<RebindStatus>| {
+                            start_rebind(action, &mut commands, &mut status);
+                        },
+                    ),
+                ),
+            ],
+        )
+    });
+
+    (
+        Name::new("Keybindings"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Px(10.0),
+            ..default()
+        },
+        children![
+            (
+                Name::new("Keybindings Grid"),
+                Node {
+                    display: Display::Grid,
+                    row_gap: Px(6.0),
+                    column_gap: Px(30.0),
+                    grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+                    max_height: Vh(40.0),
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(rows)),
+            ),
+            (label(""), RebindStatusLabel),
+        ],
     )
 }
 
@@ -194,6 +418,11 @@ fn core_grid() -> impl Bundle {
             music_volume(),
             label("Sfx"),
             sfx_volume(),
+            label("Language"),
+            (
+                knobs_container(),
+                children![(LanguageButton, btn_small("", next_language))],
+            ),
         ],
     )
 }
@@ -245,6 +474,9 @@ fn sfx_volume() -> impl Bundle {
 #[reflect(Component)]
 struct SfxVolumeLabel;
 
+#[derive(Component)]
+struct LanguageButton;
+
 fn knob_label(knob_label: impl Component) -> impl Bundle {
     (
         Node {
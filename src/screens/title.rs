@@ -1,4 +1,5 @@
 use super::*;
+use crate::loading::packs::ContentPacks;
 
 /// This plugin is responsible for the game menu
 /// The menu is only drawn during the State [`Screen::Title`] and is removed when that state is exited
@@ -6,37 +7,101 @@ pub fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), setup_menu);
 }
 
-fn setup_menu(mut commands: Commands, sources: Res<AudioSources>, settings: Res<Settings>, level_list: Res<LevelList>, level_configs: Res<Assets<LevelConfig>>, editor_mode: Res<EditorMode>, mut next_screen: ResMut<NextState<Screen>>, cfg: Res<Config>) {
+fn setup_menu(
+    mut commands: Commands,
+    sources: Res<AudioSources>,
+    settings: Res<Settings>,
+    level_list: Res<LevelList>,
+    level_configs: Res<Assets<LevelConfig>>,
+    editor_mode: Res<EditorMode>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    packs: Res<ContentPacks>,
+    level_lists: Res<Assets<LevelList>>,
+    assets: Res<AssetServer>,
+) {
     if editor_mode.0 {
         next_screen.set(Screen::Gameplay);
-        return
+        return;
     }
-    commands.insert_resource(avian2d::prelude::Gravity(Vec2::NEG_Y * 9.81 * cfg.physics.gravity));
 
-    let levels: Vec<_> = level_list.0.iter().enumerate().map(|(i, h)| {
-        let name = level_configs.get(h).unwrap().name.clone();
-        btn_big(name, move |_: Trigger<OnPress>, mut cmds: Commands, level_list: Res<LevelList>, mut next_screen: ResMut<NextState<Screen>>,| {
-            next_screen.set(Screen::Gameplay);
-            cmds.insert_resource(LoadedLevel(level_list.0[i].clone()));
+    // the resource holds a pack's levels after playing one
+    let level_list = level_lists.get(&packs.base_levels).unwrap_or(&level_list);
+    let levels: Vec<_> = level_list
+        .0
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            let name = level_configs.get(h).unwrap().name.clone();
+            btn_big(
+                name,
+                move |_: Trigger<OnPress>,
+                      mut cmds: Commands,
+                      packs: Res<ContentPacks>,
+                      level_lists: Res<Assets<LevelList>>,
+                      mut next_screen: ResMut<NextState<Screen>>| {
+                    // back to the game's own campaign after playing a pack
+                    let Some(level_list) = level_lists.get(&packs.base_levels) else {
+                        return;
+                    };
+                    next_screen.set(Screen::Gameplay);
+                    cmds.insert_resource(LoadedLevel(level_list.0[i].clone()));
+                    cmds.insert_resource(level_list.clone());
+                },
+            )
         })
-    }).collect();
-    commands.spawn((
-        StateScoped(Screen::Title),
-        ui_root("Title"),
-        // Crutch until we can use #cfg in children![] macro
-        // https://github.com/bevyengine/bevy/issues/18953
-    )).with_children(|parent| {
-        parent.spawn(BackgroundColor(TRANSLUCENT));
-        for level in levels {
-            parent.spawn(level);
-        }
-        parent.spawn(btn_big("Credits", to::credits));
-        parent.spawn(btn_big("Settings", to::settings));
-        #[cfg(not(target_family = "wasm"))]
-        parent.spawn(
-            btn_big("Exit", exit_app)
-        );
-    });
+        .collect();
+    // packs still loading when the menu opens show up the next time it does
+    let campaigns: Vec<_> = packs
+        .packs
+        .iter()
+        .enumerate()
+        .filter(|(_, pack)| {
+            pack.levels
+                .as_ref()
+                .is_some_and(|h| assets.is_loaded_with_dependencies(h))
+        })
+        .map(|(i, pack)| {
+            btn_big(
+                pack.name.clone(),
+                move |_: Trigger<OnPress>,
+                      mut cmds: Commands,
+                      packs: Res<ContentPacks>,
+                      level_lists: Res<Assets<LevelList>>,
+                      mut next_screen: ResMut<NextState<Screen>>| {
+                    let level_list = packs.packs[i]
+                        .levels
+                        .as_ref()
+                        .and_then(|h| level_lists.get(h));
+                    let Some(first) = level_list.and_then(|list| list.0.first()) else {
+                        return;
+                    };
+                    next_screen.set(Screen::Gameplay);
+                    cmds.insert_resource(LoadedLevel(first.clone()));
+                    cmds.insert_resource(level_list.unwrap().clone());
+                },
+            )
+        })
+        .collect();
+    commands
+        .spawn((
+            StateScoped(Screen::Title),
+            ui_root("Title"),
+            // Crutch until we can use #cfg in children![] macro
+            // https://github.com/bevyengine/bevy/issues/18953
+        ))
+        .with_children(|parent| {
+            parent.spawn(BackgroundColor(TRANSLUCENT));
+            for level in levels {
+                parent.spawn(level);
+            }
+            for campaign in campaigns {
+                parent.spawn(campaign);
+            }
+            parent.spawn(btn_big("Credits", to::credits));
+            parent.spawn(btn_big("Settings", to::settings));
+            #[cfg(not(target_family = "wasm"))]
+            parent.spawn(btn_big("Exit", exit_app));
+        });
     commands.spawn((
         StateScoped(Screen::Title),
         music_looping(sources.menu.clone(), settings.music()),
@@ -0,0 +1,131 @@
+use super::*;
+
+fn part(name: &str, position: Vec3) -> MachinePartType {
+    MachinePartType {
+        name: name.to_string(),
+        context: PlacementContext {
+            position,
+            ..default()
+        },
+    }
+}
+
+fn spawn_request(part_type: MachinePartType, free: bool) -> MachinePartRequest {
+    MachinePartRequest::SpawnMachinePart(MachinePartSpawnRequest {
+        location: part_type.context.position,
+        part_type,
+        initial_part: false,
+        free,
+    })
+}
+
+/// Tea that satisfies `recipe`.
+fn brewed(recipe: Recipe) -> ParticleContents {
+    let amount = |wanted| if wanted { 1.0 } else { 0.0 };
+    ParticleContents {
+        heat: 1.0,
+        tea: 1.0,
+        sugar: amount(recipe.sweet),
+        milk: amount(recipe.milky),
+    }
+}
+
+fn sensors(app: &mut App) -> Vec<(Entity, Recipe)> {
+    app.world_mut()
+        .query::<(Entity, &TeaSensor)>()
+        .iter(app.world())
+        .map(|(entity, sensor)| (entity, sensor.0))
+        .collect()
+}
+
+fn zen_points(app: &App) -> u32 {
+    app.world().resource::<AvailableZenPoints>().amount()
+}
+
+#[test]
+fn brewed_tea_satisfies_black_tea_mug() {
+    let mut app = test_app();
+    request(
+        &mut app,
+        spawn_request(part("Black Tea Mug", Vec3::ZERO), true),
+    );
+    float(&mut app);
+    start_machine(&mut app);
+
+    let sensors = sensors(&mut app);
+    let [(sensor, recipe)] = sensors[..] else {
+        panic!("the mug should have one sensor");
+    };
+    assert!(!app.world().entity(sensor).contains::<Satisfied>());
+
+    fill_sensor(&mut app, sensor, brewed(recipe));
+    update(&mut app, 10);
+    assert!(app.world().entity(sensor).contains::<Satisfied>());
+}
+
+#[test]
+fn plain_water_does_not_satisfy_black_tea_mug() {
+    let mut app = test_app();
+    request(
+        &mut app,
+        spawn_request(part("Black Tea Mug", Vec3::ZERO), true),
+    );
+    float(&mut app);
+    start_machine(&mut app);
+
+    let (sensor, _) = sensors(&mut app)[0];
+    fill_sensor(&mut app, sensor, ParticleContents::default());
+    update(&mut app, 10);
+    assert!(!app.world().entity(sensor).contains::<Satisfied>());
+}
+
+#[test]
+fn erasing_a_part_refunds_its_cost() {
+    let mut app = test_app();
+    load_level(&mut app, 0);
+    let start = zen_points(&app);
+    let cost = app.world().resource::<MachinePartConfigByType>().0["Wood Ramp"].cost;
+    assert!(cost > 0 && cost <= start);
+
+    let ramp = part("Wood Ramp", Vec3::new(0.0, 64.0, 0.0));
+    request(&mut app, spawn_request(ramp.clone(), false));
+    assert_eq!(zen_points(&app), start - cost);
+
+    let ramps = |app: &mut App| {
+        app.world_mut()
+            .query_filtered::<(Entity, &MachinePartType), With<SpawnedMachinePart>>()
+            .iter(app.world())
+            .filter(|(_, part_type)| **part_type == ramp)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>()
+    };
+    let placed = ramps(&mut app);
+    assert_eq!(placed.len(), 1);
+
+    request(&mut app, MachinePartRequest::EraseMachinePart(placed[0]));
+    assert_eq!(zen_points(&app), start);
+    assert!(ramps(&mut app).is_empty());
+}
+
+#[test]
+fn finishing_the_last_level_finishes_the_game() {
+    let mut app = test_app();
+    let last = app.world().resource::<LevelList>().0.len() - 1;
+    load_level(&mut app, last);
+    float(&mut app);
+    start_machine(&mut app);
+    watch_modals(&mut app);
+
+    let sensors = sensors(&mut app);
+    assert!(!sensors.is_empty(), "the last level should have cups");
+    for (sensor, recipe) in sensors {
+        fill_sensor(&mut app, sensor, brewed(recipe));
+    }
+    update(&mut app, 10);
+
+    let shown = &app.world().resource::<ShownModals>().0;
+    assert!(
+        matches!(shown[..], [Modal::GameFinished]),
+        "shown: {shown:?}"
+    );
+}
@@ -0,0 +1,200 @@
+//! Gameplay tests on an [`App`] without a window, renderer or audio.
+//!
+//! [`test_app`] runs the simulation plugins, physics included, on content from `assets/`.
+//! Every update advances time by one 60 fps frame, so physics steps the same way on every
+//! machine.
+
+mod gameplay;
+
+use crate::{
+    game::{
+        self, ParticleLayer, environment::Environment, inspector::ParticleView,
+        machine_parts::machine_part_config_by_type::MachinePartConfigByTypeLoader,
+        physics::PhysicsState, replay::GameRng,
+    },
+    prelude::*,
+    screens::gameplay::ModifiedLevel,
+};
+use avian2d::prelude::*;
+use bevy::{
+    asset::LoadState, scene::ScenePlugin, state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+use bevy_seedling::sample::Sample;
+use std::{path::Path, time::Duration};
+
+const ASSETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+
+/// Updates to wait for an asset before giving up on it.
+const LOAD_TIMEOUT: u32 = 600;
+
+/// Time every update advances by.
+pub const FRAME: Duration = Duration::from_nanos(16_666_667);
+
+/// The game's simulation on the screen it runs on, with `config.ron`, `machine_parts.ron` and
+/// the levels in `levels.ron` loaded. Physics starts paused, like it does in the game.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        assets(),
+        StatesPlugin,
+        TransformPlugin,
+        ScenePlugin,
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>()
+    .insert_state(Screen::Gameplay)
+    .init_resource::<ResourceHandles>()
+    .init_resource::<ModifiedLevel>()
+    .init_resource::<ParticleView>()
+    .init_resource::<GameRng>()
+    .add_plugins((
+        game::physics::plugin,
+        game::environment::plugin,
+        game::levels::plugin,
+        game::particles::plugin,
+        game::heat::plugin,
+        game::tea::plugin,
+        game::victory::plugin,
+        MachinePartEventsPlugin,
+        MachinePartSpawnerPlugin,
+        CurrencyPlugin,
+    ));
+
+    let levels = level_list(&mut app);
+    app.insert_resource(config())
+        .insert_resource(machine_parts())
+        .insert_resource(levels);
+    app.update();
+    app
+}
+
+fn assets() -> AssetPlugin {
+    AssetPlugin {
+        file_path: ASSETS.into(),
+        watch_for_changes_override: Some(false),
+        ..default()
+    }
+}
+
+fn read(path: &str) -> Vec<u8> {
+    std::fs::read(Path::new(ASSETS).join(path)).unwrap_or_else(|e| panic!("{path}: {e}"))
+}
+
+pub fn config() -> Config {
+    ron::de::from_bytes(&read("config.ron")).expect("config.ron")
+}
+
+/// `machine_parts.ron` as [`MachinePartConfigByTypeLoader`] loads it in the game, colliders
+/// included. The game's systems need the parts on every update, so they're loaded by an app
+/// of their own. Sounds have no loader without audio, so it waits for the parts themselves
+/// rather than their dependencies.
+pub fn machine_parts() -> MachinePartConfigByType {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, assets(), ImagePlugin::default()))
+        .init_asset::<Sample>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<MachinePartConfigByType>()
+        .register_asset_loader(MachinePartConfigByTypeLoader);
+    // the image loader is registered once plugins finish
+    app.finish();
+    app.cleanup();
+
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<MachinePartConfigByType>("machine_parts.ron");
+    for _ in 0..LOAD_TIMEOUT {
+        match app.world().resource::<AssetServer>().load_state(&handle) {
+            LoadState::Loaded => {
+                let parts = app.world().resource::<Assets<MachinePartConfigByType>>();
+                return parts.get(&handle).expect("machine_parts.ron").clone();
+            }
+            LoadState::Failed(error) => panic!("machine_parts.ron: {error}"),
+            _ => app.update(),
+        }
+    }
+    panic!("machine_parts.ron didn't load in {LOAD_TIMEOUT} updates");
+}
+
+/// The campaign from `levels.ron`, added straight to [`Assets<LevelConfig>`].
+fn level_list(app: &mut App) -> LevelList {
+    let paths: Vec<String> = ron::de::from_bytes(&read("levels.ron")).expect("levels.ron");
+    let mut levels = app.world_mut().resource_mut::<Assets<LevelConfig>>();
+    LevelList(
+        paths
+            .iter()
+            .map(|path| levels.add(ron::de::from_bytes::<LevelConfig>(&read(path)).unwrap()))
+            .collect(),
+    )
+}
+
+pub fn update(app: &mut App, frames: u32) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// Loads the level at `index` in `levels.ron` and spawns its initial parts.
+pub fn load_level(app: &mut App, index: usize) {
+    let level = app.world().resource::<LevelList>().0[index].clone();
+    app.insert_resource(LoadedLevel(level));
+    update(app, 2);
+}
+
+pub fn request(app: &mut App, request: MachinePartRequest) {
+    app.world_mut().send_event(request);
+    update(app, 2);
+}
+
+/// Turns gravity off for the level, so parts and particles stay where they are put.
+pub fn float(app: &mut App) {
+    app.world_mut().resource_mut::<Environment>().gravity = Vec2::ZERO;
+    update(app, 1);
+}
+
+/// Unpauses physics and lets it place the colliders.
+pub fn start_machine(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<NextState<PhysicsState>>()
+        .set(PhysicsState::Running);
+    update(app, 5);
+}
+
+/// Puts twice the particles a sensor needs in the middle of its collider. They neither
+/// collide with each other nor sleep, so the sensor keeps counting them.
+pub fn fill_sensor(app: &mut App, sensor: Entity, contents: ParticleContents) {
+    let aabb = app
+        .world()
+        .get::<ColliderAabb>(sensor)
+        .expect("sensor collider");
+    let center = (aabb.min + aabb.max) / 2.0;
+    let radius = app.world().resource::<Config>().droplet_radius;
+    for _ in 0..2 * MIN_SENSOR_PARTICLES {
+        app.world_mut().spawn((
+            LevelObject,
+            Transform::from_translation(center.extend(0.0)),
+            RigidBody::Dynamic,
+            Collider::circle(radius),
+            CollisionLayers::new(ParticleLayer::Fluid, [ParticleLayer::Default]),
+            SleepingDisabled,
+            Particle {
+                lifetime: Timer::from_seconds(60.0, TimerMode::Once),
+                contents,
+            },
+        ));
+    }
+}
+
+/// Modals opened since [`watch_modals`] was called.
+#[derive(Resource, Default)]
+pub struct ShownModals(pub Vec<Modal>);
+
+pub fn watch_modals(app: &mut App) {
+    app.init_resource::<ShownModals>().add_observer(
+        |trigger: Trigger<OnNewModal>, mut shown: ResMut<ShownModals>| {
+            shown.0.push(trigger.event().0.clone());
+        },
+    );
+}
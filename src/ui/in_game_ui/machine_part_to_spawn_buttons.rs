@@ -1,5 +1,8 @@
 use crate::prelude::Val::Percent;
-use crate::{game::physics::PhysicsState, prelude::*};
+use crate::{
+    game::{physics::PhysicsState, tutorial::TutorialAnchor},
+    prelude::*,
+};
 use bevy::{prelude::*, ui::Val::*};
 
 pub struct MachinePartToSpawnButtonsPlugin;
@@ -22,8 +25,9 @@ impl Plugin for MachinePartToSpawnButtonsPlugin {
     }
 }
 
+/// The row of part buttons, in the order the level lists its parts.
 #[derive(Component)]
-struct MachinePartButtonNode;
+pub struct MachinePartButtonNode;
 
 fn spawn_part_picking_buttons(
     mut commands: Commands,
@@ -41,20 +45,34 @@ fn spawn_part_picking_buttons(
     let config = level_configs.get(&loaded_level.0).unwrap();
     let level = GameLevel::Loaded(config.name.clone());
 
+    // the editor offers every part, by name, a level keeps the order it lists them in
+    let parts = if editor_mode.0 {
+        let mut names: Vec<_> = machine_parts.0.keys().cloned().collect();
+        names.sort();
+        names
+    } else {
+        config.available_machine_parts.clone()
+    };
+
     let mut buttons = Vec::new();
-    for part in machine_parts.0.keys() {
-        if editor_mode.0 || config.available_machine_parts.contains(part) {
+    for part in &parts {
+        if machine_parts.0.contains_key(part) {
             // You need to provide the correct PlacementContext value for each part.
+            let display_key = machine_parts.0[part].display_key(part).to_string();
             let button_bundle = btn_with_machine_part_type(
                 MachinePartType {
                     name: part.clone(),
                     context: PlacementContext::default(),
                 }, // Replace PlacementContext::default() with the correct context if needed
-                part,
+                display_key,
                 level.clone(),
                 &machine_part_configs,
             );
-            buttons.push(commands.spawn(button_bundle).id());
+            buttons.push(
+                commands
+                    .spawn((button_bundle, TutorialAnchor::new(part)))
+                    .id(),
+            );
         }
     }
 
@@ -71,6 +89,7 @@ fn spawn_part_picking_buttons(
                 ..default()
             },
             MachinePartButtonNode,
+            TutorialAnchor::new("parts"),
         ))
         .add_children(&buttons)
         .with_children(|parent| {
@@ -1,3 +1,4 @@
+use crate::game::camera::CameraBounds;
 use crate::prelude::tags::MachinesSpawnArea;
 use crate::prelude::*;
 use bevy::prelude::*;
@@ -13,6 +14,7 @@ impl Plugin for MachinesSpawnAreaPlugin {
         .add_systems(
             Update,
             (
+                fit_machines_area.run_if(resource_exists_and_changed::<CameraBounds>),
                 change_preview_sprite.run_if(resource_exists_and_changed::<PickingState>),
                 change_preview_visibility.run_if(resource_exists_and_changed::<PickingState>),
             )
@@ -23,34 +25,49 @@ impl Plugin for MachinesSpawnAreaPlugin {
     }
 }
 
+/// A unit square scaled to [`CameraBounds`], so parts can be placed anywhere in the level
+/// however the camera is moved.
 fn spawn_machines_area(
-    windows: Query<&Window>,
+    bounds: Res<CameraBounds>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut commands: Commands,
 ) {
-    let maybe_window = windows.single();
-    if let Ok(window) = maybe_window {
-        let square = meshes.add(Rectangle::new(
-            window.resolution.width(),
-            window.resolution.height(),
-        ));
-        let no_color = materials.add(Color::NONE);
-        commands.spawn((
-            Transform::from_xyz(0.0, 0.0, -140.0),
-            StateScoped(Screen::Gameplay),
-            MachinesSpawnArea,
-            Mesh2d(square),
-            MeshMaterial2d(no_color),
-        ));
+    let square = meshes.add(Rectangle::new(1.0, 1.0));
+    let no_color = materials.add(Color::NONE);
+    commands.spawn((
+        area_transform(&bounds),
+        StateScoped(Screen::Gameplay),
+        MachinesSpawnArea,
+        Mesh2d(square),
+        MeshMaterial2d(no_color),
+    ));
+}
+
+fn fit_machines_area(
+    bounds: Res<CameraBounds>,
+    mut areas: Query<&mut Transform, With<MachinesSpawnArea>>,
+) {
+    for mut transform in &mut areas {
+        *transform = area_transform(&bounds);
     }
 }
 
+fn area_transform(bounds: &CameraBounds) -> Transform {
+    Transform::from_translation(bounds.center().extend(-140.0))
+        .with_scale(bounds.size().extend(1.0))
+}
+
 #[derive(Component)]
 struct MachinePartPreview;
 
 fn spawn_preview(mut commands: Commands) {
-    commands.spawn((StateScoped(Screen::Gameplay), MachinePartPreview, Transform::default(), Visibility::Hidden));
+    commands.spawn((
+        StateScoped(Screen::Gameplay),
+        MachinePartPreview,
+        Transform::default(),
+        Visibility::Hidden,
+    ));
 }
 
 fn on_machine_spawn_area_click(
@@ -71,7 +88,7 @@ fn on_machine_spawn_area_click(
                     .with_z(MACHINE_PARTS_BASIC_Z_LAYER);
 
                 let source = sounds.place_piece.clone();
-                commands.spawn(sfx(source, settings.sfx()));
+                commands.spawn(spatial_sfx(source, settings.sfx(), hit_position));
                 machine_part_request_writer.write(MachinePartRequest::SpawnMachinePart(
                     MachinePartSpawnRequest {
                         location: ((hit_position / MACHINE_PARTS_GRID_SCALE).round()
@@ -104,14 +121,16 @@ fn change_preview_sprite(
     mut commands: Commands,
     picking_state: Res<PickingState>,
     machine_part_config_by_type: Res<MachinePartConfigByType>,
-    preview: Single<Entity, With<MachinePartPreview>>,
+    preview: Single<(Entity, &mut Transform), With<MachinePartPreview>>,
 ) {
+    let (preview, mut transform) = preview.into_inner();
     if let PickingState::Placing(ty) = &*picking_state {
         if let Some(part_config) = machine_part_config_by_type.0.get(&ty.name) {
-            commands.entity(*preview).despawn_related::<Children>();
+            commands.entity(preview).despawn_related::<Children>();
             let index = part_config.texture_info.frames.frames() * ty.context.rotation_index;
+            transform.rotation = ty.context.rotation();
 
-            part_config.spawn_sprites(index, commands.entity(*preview));
+            part_config.spawn_sprites(index, commands.entity(preview));
         }
     }
 }
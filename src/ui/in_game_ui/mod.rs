@@ -2,14 +2,20 @@ use bevy::prelude::*;
 
 pub mod machine_part_to_spawn_buttons;
 pub mod machines_spawn_area;
+pub mod recipe_hud;
 
 use machine_part_to_spawn_buttons::*;
 use machines_spawn_area::*;
+use recipe_hud::*;
 
 pub struct InGameUiPlugin;
 
 impl Plugin for InGameUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((MachinesSpawnAreaPlugin, MachinePartToSpawnButtonsPlugin));
+        app.add_plugins((
+            MachinesSpawnAreaPlugin,
+            MachinePartToSpawnButtonsPlugin,
+            RecipeHudPlugin,
+        ));
     }
 }
@@ -0,0 +1,241 @@
+//! One row per [`TeaSensor`] in the corner of the gameplay screen, showing how far its
+//! contents are from the recipe. Rows are spawned once per sensor and only their values
+//! are updated afterwards.
+
+use crate::{
+    game::tea::{MIN_SENSOR_PARTICLES, Recipe, SensorReading, TeaSensor},
+    prelude::*,
+    screens::gameplay::ScoreLabel,
+};
+use bevy::prelude::*;
+
+/// Width of a contents bar, the full bar being 1.0.
+const BAR_WIDTH: f32 = 96.0;
+const FONT_SIZE: f32 = 14.0;
+
+pub struct RecipeHudPlugin;
+
+impl Plugin for RecipeHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_checks).add_systems(
+            Update,
+            (spawn_sensor_huds, despawn_sensor_huds, update_sensor_huds)
+                .chain()
+                .run_if(in_state(Screen::Gameplay)),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct CheckboxImages {
+    empty: Handle<Image>,
+    checked: Handle<Image>,
+}
+
+fn load_checks(mut commands: Commands, assets: Res<AssetServer>) {
+    let checked = assets.load("textures/icons/check_tick.png");
+    let empty = assets.load("textures/icons/check_empty.png");
+    commands.insert_resource(CheckboxImages { checked, empty });
+}
+
+/// The HUD row showing this sensor.
+#[derive(Component)]
+struct SensorHudRow(Entity);
+
+/// A HUD row and the entities in it that change.
+#[derive(Component)]
+struct SensorHud {
+    sensor: Entity,
+    check: Entity,
+    count: Entity,
+    /// Fill of the tea, milk and sugar bars.
+    bars: [Entity; 3],
+    heat: Entity,
+}
+
+fn spawn_sensor_huds(
+    mut commands: Commands,
+    images: Res<CheckboxImages>,
+    sensors: Query<(Entity, &TeaSensor, Option<&Name>), Without<SensorHudRow>>,
+    score_area: Single<Entity, With<ScoreLabel>>,
+) {
+    for (sensor, TeaSensor(recipe, icon), name) in &sensors {
+        let name = name
+            .map(Name::to_string)
+            .unwrap_or_else(|| "Tea".to_string());
+        let check = commands.spawn(ImageNode::new(images.empty.clone())).id();
+        let header = commands
+            .spawn(Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .add_children(&[check])
+            .with_children(|parent| {
+                parent.spawn(ImageNode::new(icon.clone()));
+                parent.spawn(label(name));
+            })
+            .id();
+
+        let count = commands.spawn(small_text("")).id();
+        let (tea_row, tea) = bar(&mut commands, "tea", TEA_THRESHOLD);
+        let milk = if recipe.milky { "milk" } else { "no milk" };
+        let (milk_row, milk) = bar(&mut commands, milk, MILK_THRESHOLD);
+        let sugar = if recipe.sweet { "sugar" } else { "no sugar" };
+        let (sugar_row, sugar) = bar(&mut commands, sugar, SUGAR_THRESHOLD);
+        let heat = commands.spawn(small_text("")).id();
+
+        let row = commands
+            .spawn((
+                Name::new("Sensor HUD"),
+                SensorHud {
+                    sensor,
+                    check,
+                    count,
+                    bars: [tea, milk, sugar],
+                    heat,
+                },
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    margin: UiRect::bottom(Px(6.0)),
+                    ..default()
+                },
+            ))
+            .add_children(&[header, count, tea_row, milk_row, sugar_row, heat])
+            .id();
+        commands.entity(*score_area).add_child(row);
+        commands.entity(sensor).insert(SensorHudRow(row));
+    }
+}
+
+/// Localized text, an empty `key` leaves it to [`update_sensor_huds`].
+fn small_text(key: impl Into<String>) -> impl Bundle {
+    let key = key.into();
+    (
+        Text::new(key.clone()),
+        TextFont::from_font_size(FONT_SIZE),
+        LocalizedText::new(key),
+        Pickable::IGNORE,
+    )
+}
+
+/// A labelled bar with a tick at `threshold`. Returns the row and the bar's fill.
+fn bar(commands: &mut Commands, name: impl Into<String>, threshold: f32) -> (Entity, Entity) {
+    let fill = commands
+        .spawn((
+            Node {
+                width: Percent(0.0),
+                height: Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(FIRE),
+        ))
+        .id();
+    let track = commands
+        .spawn((
+            Node {
+                width: Px(BAR_WIDTH),
+                height: Px(8.0),
+                ..default()
+            },
+            BackgroundColor(GRAY),
+        ))
+        .add_child(fill)
+        .with_child((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Percent(threshold * 100.0),
+                width: Px(2.0),
+                height: Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(WHITEISH),
+        ))
+        .id();
+    let row = commands
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Px(6.0),
+            ..default()
+        })
+        .with_child((
+            small_text(name),
+            Node {
+                width: Px(64.0),
+                ..default()
+            },
+        ))
+        .add_child(track)
+        .id();
+    (row, fill)
+}
+
+fn despawn_sensor_huds(
+    mut commands: Commands,
+    rows: Query<(Entity, &SensorHud)>,
+    sensors: Query<(), With<TeaSensor>>,
+) {
+    for (row, hud) in &rows {
+        if !sensors.contains(hud.sensor) {
+            commands.entity(row).despawn();
+        }
+    }
+}
+
+fn update_sensor_huds(
+    images: Res<CheckboxImages>,
+    localization: Localization,
+    sensors: Query<(&TeaSensor, Ref<SensorReading>, Ref<SensorHudRow>)>,
+    rows: Query<&SensorHud>,
+    mut texts: Query<&mut Text>,
+    mut checks: Query<&mut ImageNode>,
+    mut fills: Query<(&mut Node, &mut BackgroundColor)>,
+) {
+    for (TeaSensor(recipe, _), reading, row) in &sensors {
+        if !reading.is_changed() && !row.is_added() && !localization.is_changed() {
+            continue;
+        }
+        let Ok(hud) = rows.get(row.0) else {
+            continue;
+        };
+
+        if let Ok(mut check) = checks.get_mut(hud.check) {
+            check.image = if recipe.is_satisfied_by(&reading) {
+                images.checked.clone()
+            } else {
+                images.empty.clone()
+            };
+        }
+        if let Ok(mut text) = texts.get_mut(hud.count) {
+            text.0 = localization.format(
+                "particles {count}/{needed}",
+                &[
+                    ("count", &reading.count.to_string()),
+                    ("needed", &MIN_SENSOR_PARTICLES.to_string()),
+                ],
+            );
+        }
+        if let Ok(mut text) = texts.get_mut(hud.heat) {
+            let heat = format!("{:.2}", reading.average.heat);
+            text.0 = localization.format("heat {heat}", &[("heat", &heat)]);
+        }
+
+        for (fill, ok, value) in hud.bars.iter().zip(conditions(recipe, &reading)) {
+            if let Ok((mut node, mut color)) = fills.get_mut(*fill) {
+                node.width = Percent(value.clamp(0.0, 1.0) * 100.0);
+                color.0 = if ok { LIGHT_GREEN } else { FIRE };
+            }
+        }
+    }
+}
+
+/// Whether tea, milk and sugar match the recipe, with their average amounts.
+fn conditions(recipe: &Recipe, reading: &SensorReading) -> [(bool, f32); 3] {
+    let avg = reading.average;
+    [
+        (avg.is_tea(), avg.tea),
+        (avg.is_milky() == recipe.milky, avg.milk),
+        (avg.is_sweet() == recipe.sweet, avg.sugar),
+    ]
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::localization::LocalizedText;
 use std::borrow::Cow;
 
 #[derive(Debug, Clone, Bundle)]
@@ -129,7 +130,11 @@ impl Opts {
     }
     pub fn into_text_bundle(self) -> impl Bundle {
         match &self.inner {
-            WidgetContent::Text(c) => (c.clone(), self.bg_color),
+            WidgetContent::Text(c) => (
+                c.clone(),
+                self.bg_color,
+                LocalizedText::new(c.text.0.clone()),
+            ),
             _ => unreachable!("Spawning text bundle on non text content"),
         }
     }
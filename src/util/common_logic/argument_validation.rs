@@ -66,9 +66,9 @@ mod tests {
         let bigger_value = 4.0;
         let smaller_value = -1.0;
 
-        let original_outcome = clamp_and_notify(original_value, smaller_value, bigger_value);
-        let bigger_outcome = clamp_and_notify(original_value, bigger_value, bigger_value);
-        let smaller_outcome = clamp_and_notify(original_value, smaller_value, smaller_value);
+        let original_outcome = clamped(original_value, smaller_value, bigger_value, false);
+        let bigger_outcome = clamped(original_value, bigger_value, bigger_value, false);
+        let smaller_outcome = clamped(original_value, smaller_value, smaller_value, false);
 
         assert_eq!(original_value, original_outcome);
         assert_eq!(bigger_value, bigger_outcome);
@@ -79,8 +79,8 @@ mod tests {
     fn test_truncated_if_at_limit() {
         let vec = vec![1, 2, 3];
 
-        let truncated_vec = truncated_if_at_limit(vec.clone(), 2);
-        let cloned_vec = truncated_if_at_limit(vec.clone(), 4);
+        let truncated_vec = truncated_if_at_limit(vec.clone(), 2, false);
+        let cloned_vec = truncated_if_at_limit(vec.clone(), 4, false);
 
         assert_eq!(truncated_vec, vec!(1, 2));
         assert_eq!(vec, cloned_vec);
@@ -11,10 +11,11 @@ impl<T: PartialOrd + Copy + rand::distributions::uniform::SampleUniform> RandomR
         Self { min, max }
     }
 
-    pub fn random(&self) -> Option<T> {
+    /// Draws from `rng`, pass [`GameRng`](crate::game::replay::GameRng) for anything that
+    /// touches the simulation so replays stay reproducible.
+    pub fn random(&self, rng: &mut impl Rng) -> Option<T> {
         if self.min < self.max {
-            let mut random = rand::thread_rng();
-            Some(random.gen_range(self.min..self.max))
+            Some(rng.gen_range(self.min..self.max))
         } else {
             None
         }
@@ -34,10 +34,10 @@ pub fn item_to_index<T: PartialEq>(item_to_find: &T, vec_to_find_in: &[T]) -> Op
     vec_to_find_in.iter().position(|x| *x == *item_to_find)
 }
 
-pub fn random_value<T: Clone>(vec_ref: &Vec<T>) -> Option<T> {
-    random_index(vec_ref).map(|index| vec_ref[index].clone())
+pub fn random_value<T: Clone>(vec_ref: &Vec<T>, rng: &mut impl rand::Rng) -> Option<T> {
+    random_index(vec_ref, rng).map(|index| vec_ref[index].clone())
 }
 
-pub fn random_index<T>(vec_ref: &[T]) -> Option<usize> {
-    RandomRange::new(0, vec_ref.len()).random()
+pub fn random_index<T>(vec_ref: &[T], rng: &mut impl rand::Rng) -> Option<usize> {
+    RandomRange::new(0, vec_ref.len()).random(rng)
 }
@@ -0,0 +1,552 @@
+//! Shapes found in images: collider outlines of the solid pixels and the spots a vessel's
+//! particles fill.
+//!
+//! Everything here works on an [`RgbaBuffer`] rather than a bevy [`Image`], so it runs the same
+//! in the loader, in `bake-colliders` and in tests.
+
+use crate::prelude::*;
+use avian2d::{
+    parry::{
+        math::{Isometry, Point},
+        shape::{Compound, SharedShape},
+    },
+    prelude::*,
+};
+use bevy::color::ColorToPacked;
+use geo::{BooleanOps, Coord, CoordsIter, LineString, MultiPolygon, Vector2DOps};
+use itertools::Itertools as _;
+use std::collections::VecDeque;
+
+/// An 8 bit RGBA image, rows from top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbaBuffer {
+    pub size: UVec2,
+    pub data: Vec<u8>,
+}
+
+impl RgbaBuffer {
+    pub fn new(size: UVec2, data: Vec<u8>) -> Self {
+        assert_eq!(
+            data.len(),
+            (size.x * size.y * 4) as usize,
+            "not a {size} RGBA image"
+        );
+        Self { size, data }
+    }
+
+    /// The pixels of `image`, in sRGB whatever its texture format.
+    pub fn from_image(image: &Image) -> Self {
+        let size = image.size();
+        let data = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let color = image.get_color_at(x, y).unwrap_or(Color::NONE);
+                color.to_srgba().to_u8_array()
+            })
+            .collect();
+        Self { size, data }
+    }
+
+    /// Alpha of the pixel at `x, y`, `None` outside the image.
+    pub fn alpha(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.size.x || y >= self.size.y {
+            return None;
+        }
+        let i = (y * self.size.x + x) as usize * 4 + 3;
+        Some(self.data[i] as f32 / 255.0)
+    }
+}
+
+/// Generates colliders for each of the `rotations` rows the image is split into.
+pub fn rotation_colliders(
+    image: &RgbaBuffer,
+    rotations: u32,
+    settings: &ColliderGenSettings,
+) -> Vec<Vec<Compound>> {
+    let size = image.size;
+    let row_height = size.y / rotations;
+    (0..rotations)
+        .map(|rot| {
+            let offset = UVec2::new(0, row_height * rot);
+            let region_size = UVec2::new(size.x, row_height);
+            colliders_from_image_region(image, offset, region_size, settings)
+        })
+        .collect()
+}
+
+/// One compound per island of solid pixels in the region, centered on the region with y up.
+/// Holes inside an island are filled, only its outline becomes a collider.
+pub fn colliders_from_image_region(
+    image: &RgbaBuffer,
+    offset: UVec2,
+    size: UVec2,
+    settings: &ColliderGenSettings,
+) -> Vec<Compound> {
+    if size.x == 0 || size.y == 0 {
+        return vec![];
+    }
+    let mut pixels = vec![0.0; (size.x * size.y) as usize];
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let alpha = image.alpha(offset.x + x, offset.y + y);
+            if alpha.is_some_and(|alpha| alpha >= settings.alpha_threshold) {
+                let i = y * size.x + x;
+                pixels[i as usize] = 1.0;
+            }
+        }
+    }
+    let c = contour::ContourBuilder::new(size.x as usize, size.y as usize, false);
+    let contour = c.contours(&pixels, &[0.5]).unwrap();
+    let polygons: MultiPolygon<f64> = contour[0].clone().into_inner().0;
+    let polys = divide_reduce::<MultiPolygon<f64>>(vec![polygons], |a, b| a.union(&b))
+        .unwrap_or(MultiPolygon::new(vec![]));
+
+    let polys: Vec<_> = polys
+        .into_iter()
+        .map(|p| {
+            geo::Polygon::new(
+                removed_in_line(p.exterior(), settings.simplify_epsilon),
+                p.interiors()
+                    .iter()
+                    .map(|line| removed_in_line(line, settings.simplify_epsilon))
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let mut vhacd = VhacdParameters::default();
+    if let Some(resolution) = settings.vhacd_resolution {
+        vhacd.resolution = resolution;
+    }
+    if let Some(concavity) = settings.vhacd_concavity {
+        vhacd.concavity = concavity;
+    }
+
+    polys
+        .into_iter()
+        .filter_map(|poly| {
+            let exterior: Vec<Vec2> = poly
+                .exterior()
+                .into_iter()
+                .map(|p| {
+                    Vec2::new(
+                        p.x as f32 - size.x as f32 / 2.0,
+                        -p.y as f32 + size.y as f32 / 2.0,
+                    )
+                })
+                .collect();
+            if settings.single_convex_hull {
+                let points = exterior.iter().map(|p| Point::new(p.x, p.y)).collect_vec();
+                let hull = SharedShape::convex_hull(&points)?;
+                return Some(Compound::new(vec![(Isometry::identity(), hull)]));
+            }
+            let indices = (0..exterior.len() as u32).collect_vec();
+            let collider = Collider::convex_decomposition_with_config(
+                exterior,
+                indices.windows(2).map(|i| [i[0], i[1]]).collect_vec(),
+                &vhacd,
+            );
+            let shape = collider.shape().as_compound().unwrap();
+            Some(shape.clone())
+        })
+        .collect()
+}
+
+/// Reduces neighbours pairwise until one item is left, so each item takes part in about
+/// log2(n) reductions instead of the last one taking part in all of them.
+pub fn divide_reduce<T>(list: Vec<T>, mut reduction: impl FnMut(T, T) -> T) -> Option<T> {
+    let mut queue = VecDeque::from(list);
+
+    while queue.len() > 1 {
+        for _ in 0..(queue.len() / 2) {
+            let (one, two) = (queue.pop_front().unwrap(), queue.pop_front().unwrap());
+            queue.push_back(reduction(one, two));
+        }
+    }
+
+    queue.pop_back()
+}
+
+/// The corners of a closed outline, without the points that lie on a straight run.
+/// `epsilon` is how far from straight (1 - cos of the angle) a corner has to be to stay.
+pub fn removed_in_line(line: &LineString<f64>, epsilon: f64) -> LineString<f64> {
+    if line.coords_count() < 3 {
+        return line.clone();
+    }
+
+    let mut points = line.coords().copied();
+    let (mut a, mut b) = points.next_tuple().unwrap();
+    let mut kept = vec![];
+
+    let along_path = |a: Coord<f64>, b: Coord<f64>, c: Coord<f64>| {
+        let dir_1 = (c - b).try_normalize().unwrap_or_default();
+        let dir_2 = (b - a).try_normalize().unwrap_or_default();
+        dir_1.dot_product(dir_2) >= 1.0 - epsilon
+    };
+    // maybe add first one
+    {
+        let mut coords = line.coords().copied();
+        let b = coords.next().unwrap();
+        let c = coords.next().unwrap();
+        let _ = coords.next_back().unwrap(); // last coord is a duplicate
+        let a = coords.next_back().unwrap();
+        if !along_path(a, b, c) {
+            kept.push(b);
+        }
+    }
+
+    for p in points {
+        if !along_path(a, b, p) {
+            kept.push(b);
+        }
+        (a, b) = (b, p);
+    }
+    LineString::<f64>::new(kept)
+}
+
+/// Packs circles of `radius` pixels into the pixels that aren't fully transparent, row by row
+/// from the top left, and returns their centers relative to the image center with y up.
+///
+/// A circle centered on a pixel covers the pixels `-radius..radius` away on each axis that are
+/// within `radius` of it. The range stops short on the right and bottom, so a circle reaches
+/// one pixel further up and left than down and right. Circles never overlap each other or
+/// leave the image. Radii below 1 fit no circles.
+pub fn scan_image_for_circles(image: &RgbaBuffer, radius: i32) -> Vec<IVec2> {
+    let (width, height) = image.size.as_ivec2().into();
+    let mut valid_spawns = Vec::new();
+    if radius <= 0 {
+        return valid_spawns;
+    }
+
+    let r_squared = radius * radius;
+
+    let mut pixel_data: Vec<bool> = image.data.chunks_exact(4).map(|rgba| rgba[3] > 0).collect();
+
+    for y in radius..height {
+        for x in radius..width {
+            let mut covered = Vec::new();
+            let all_valid = (-radius..radius)
+                .cartesian_product(-radius..radius)
+                .filter(|&(dx, dy)| dx * dx + dy * dy <= r_squared)
+                .all(|(dx, dy)| {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        return false;
+                    }
+                    let idx = (ny * width + nx) as usize;
+                    covered.push(idx);
+                    pixel_data[idx]
+                });
+            if all_valid {
+                for idx in covered {
+                    pixel_data[idx] = false;
+                }
+                // Subtract half the width and height to center the spawn positions
+                valid_spawns.push(IVec2::new(x - width / 2, height / 2 - y));
+            }
+        }
+    }
+
+    valid_spawns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use avian2d::parry::{bounding_volume::Aabb, query::PointQuery};
+
+    /// An image from rows of `#` (opaque) and `.` (transparent) pixels.
+    fn image(rows: &[&str]) -> RgbaBuffer {
+        let size = UVec2::new(
+            rows.first().map_or(0, |row| row.len()) as u32,
+            rows.len() as u32,
+        );
+        let data = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .flat_map(|c| if c == '#' { [255; 4] } else { [0; 4] })
+            .collect();
+        RgbaBuffer::new(size, data)
+    }
+
+    fn solid(width: usize, height: usize) -> RgbaBuffer {
+        let row = "#".repeat(width);
+        image(&vec![row.as_str(); height])
+    }
+
+    fn aabb(compound: &Compound) -> Aabb {
+        *compound.local_aabb()
+    }
+
+    fn contains(compound: &Compound, x: f32, y: f32) -> bool {
+        compound.contains_point(&Isometry::identity(), &Point::new(x, y))
+    }
+
+    #[test]
+    fn empty_images_have_no_colliders() {
+        let settings = ColliderGenSettings::default();
+        assert!(rotation_colliders(&image(&[]), 1, &settings)[0].is_empty());
+        let clear = image(&["........"; 8]);
+        assert!(rotation_colliders(&clear, 1, &settings)[0].is_empty());
+    }
+
+    #[test]
+    fn pixels_below_the_alpha_threshold_are_empty() {
+        let mut translucent = solid(8, 8);
+        translucent
+            .data
+            .iter_mut()
+            .skip(3)
+            .step_by(4)
+            .for_each(|a| *a = 128);
+        let settings = ColliderGenSettings::default();
+        assert!(rotation_colliders(&translucent, 1, &settings)[0].is_empty());
+        let settings = ColliderGenSettings {
+            alpha_threshold: 0.5,
+            ..default()
+        };
+        assert_eq!(rotation_colliders(&translucent, 1, &settings)[0].len(), 1);
+    }
+
+    #[test]
+    fn solid_image_is_one_centered_collider() {
+        let colliders = rotation_colliders(&solid(8, 8), 1, &default());
+        let [compound] = &colliders[0][..] else {
+            panic!("expected one collider, got {}", colliders[0].len());
+        };
+        let aabb = aabb(compound);
+        assert!(aabb.center().coords.norm() < 1.0, "{aabb:?}");
+        assert!(
+            aabb.extents().x > 6.0 && aabb.extents().x <= 8.0,
+            "{aabb:?}"
+        );
+        assert!(contains(compound, 0.0, 0.0));
+    }
+
+    #[test]
+    fn holes_are_filled() {
+        let ring = image(&[
+            "##########",
+            "##########",
+            "##......##",
+            "##......##",
+            "##......##",
+            "##......##",
+            "##......##",
+            "##......##",
+            "##########",
+            "##########",
+        ]);
+        let colliders = rotation_colliders(&ring, 1, &default());
+        let [compound] = &colliders[0][..] else {
+            panic!("expected one collider, got {}", colliders[0].len());
+        };
+        assert!(contains(compound, 0.0, 0.0));
+        assert!(contains(compound, -4.0, 4.0));
+    }
+
+    #[test]
+    fn disjoint_islands_get_a_collider_each() {
+        let islands = image(&[
+            "............",
+            ".###....###.",
+            ".###....###.",
+            ".###....###.",
+            "............",
+            "............",
+            "....####....",
+            "....####....",
+            "....####....",
+            "............",
+        ]);
+        let colliders = rotation_colliders(&islands, 1, &default());
+        let mut centers = colliders[0]
+            .iter()
+            .map(|compound| aabb(compound).center())
+            .collect_vec();
+        centers.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+        let [left, bottom, right] = centers[..] else {
+            panic!("expected three colliders, got {centers:?}");
+        };
+        assert!(left.x < -2.0 && left.y > 1.0, "{left:?}");
+        assert!(bottom.x.abs() < 1.0 && bottom.y < -1.0, "{bottom:?}");
+        assert!(right.x > 2.0 && right.y > 1.0, "{right:?}");
+    }
+
+    #[test]
+    fn rotation_rows_are_generated_separately() {
+        let rows = image(&[
+            "..........",
+            "###.......",
+            "###.......",
+            "###.......",
+            "..........",
+            "..........",
+            ".......###",
+            ".......###",
+            ".......###",
+            "..........",
+        ]);
+        let colliders = rotation_colliders(&rows, 2, &default());
+        assert_eq!(colliders.len(), 2);
+        let centers = colliders
+            .iter()
+            .map(|row| {
+                let [compound] = &row[..] else {
+                    panic!("expected one collider per row, got {}", row.len());
+                };
+                aabb(compound).center()
+            })
+            .collect_vec();
+        // each row is centered on itself, not on the whole image
+        assert!(
+            centers[0].x < -1.0 && centers[0].y.abs() < 1.0,
+            "{:?}",
+            centers[0]
+        );
+        assert!(
+            centers[1].x > 1.0 && centers[1].y.abs() < 1.0,
+            "{:?}",
+            centers[1]
+        );
+    }
+
+    #[test]
+    fn single_convex_hull_makes_one_shape_per_island() {
+        let corner = image(&[
+            "##........",
+            "##........",
+            "##........",
+            "##........",
+            "##........",
+            "##........",
+            "##........",
+            "##........",
+            "##########",
+            "##########",
+        ]);
+        let settings = ColliderGenSettings {
+            single_convex_hull: true,
+            ..default()
+        };
+        let colliders = rotation_colliders(&corner, 1, &settings);
+        let [compound] = &colliders[0][..] else {
+            panic!("expected one collider, got {}", colliders[0].len());
+        };
+        assert_eq!(compound.shapes().len(), 1);
+        // the hull spans the inside of the corner
+        assert!(contains(compound, 0.0, 0.0));
+    }
+
+    #[test]
+    fn divide_reduce_uses_every_item_once() {
+        assert_eq!(divide_reduce(Vec::<i32>::new(), |a, b| a + b), None);
+        assert_eq!(divide_reduce(vec![7], |a, b| a + b), Some(7));
+        assert_eq!(divide_reduce((1..=10).collect(), |a, b| a + b), Some(55));
+
+        let mut calls = 0;
+        let joined = divide_reduce("abcde".chars().map(String::from).collect(), |a, b| {
+            calls += 1;
+            a + &b
+        });
+        let mut letters = joined.unwrap().chars().collect_vec();
+        letters.sort();
+        assert_eq!(letters, ['a', 'b', 'c', 'd', 'e']);
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn removed_in_line_keeps_only_corners() {
+        let square: LineString<f64> = vec![
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (2.0, 2.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+            (0.0, 1.0),
+            (0.0, 0.0),
+        ]
+        .into();
+        let corners: LineString<f64> = vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)].into();
+        assert_eq!(removed_in_line(&square, 0.0005), corners);
+
+        let short: LineString<f64> = vec![(0.0, 0.0), (1.0, 0.0)].into();
+        assert_eq!(removed_in_line(&short, 0.0005), short);
+    }
+
+    #[test]
+    fn removed_in_line_drops_shallow_corners_within_epsilon() {
+        let bent: LineString<f64> = vec![
+            (0.0, 0.0),
+            (10.0, 1.0),
+            (20.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 0.0),
+        ]
+        .into();
+        assert_eq!(removed_in_line(&bent, 0.0005).coords_count(), 4);
+        assert_eq!(removed_in_line(&bent, 0.05).coords_count(), 3);
+    }
+
+    #[test]
+    fn no_circles_in_empty_images() {
+        assert!(scan_image_for_circles(&image(&[]), 1).is_empty());
+        assert!(scan_image_for_circles(&image(&["....", "....", "....", "...."]), 1).is_empty());
+    }
+
+    #[test]
+    fn circles_fill_a_solid_image() {
+        let circles = scan_image_for_circles(&solid(4, 4), 1);
+        let expected = [
+            IVec2::new(-1, 1),
+            IVec2::new(1, 1),
+            IVec2::new(-1, -1),
+            IVec2::new(1, -1),
+        ];
+        assert_eq!(circles, expected);
+    }
+
+    #[test]
+    fn circles_need_a_positive_radius() {
+        assert!(scan_image_for_circles(&solid(4, 4), 0).is_empty());
+        assert!(scan_image_for_circles(&solid(4, 4), -2).is_empty());
+    }
+
+    #[test]
+    fn circles_stay_inside_the_image() {
+        assert_eq!(scan_image_for_circles(&solid(4, 4), 2), [IVec2::ZERO]);
+        assert!(scan_image_for_circles(&solid(3, 3), 2).is_empty());
+        assert_eq!(scan_image_for_circles(&solid(6, 6), 3), [IVec2::ZERO]);
+    }
+
+    #[test]
+    fn circles_of_radius_two_cover_eleven_pixels() {
+        let footprint = ["..#..", ".###.", "####.", ".###.", "....."];
+        assert_eq!(scan_image_for_circles(&image(&footprint), 2), [IVec2::ZERO]);
+        // every one of them is needed
+        for (y, row) in footprint.iter().enumerate() {
+            for x in row.match_indices('#').map(|(x, _)| x) {
+                let mut rows = footprint.map(String::from);
+                rows[y].replace_range(x..=x, ".");
+                let rows = rows.each_ref().map(String::as_str);
+                assert!(scan_image_for_circles(&image(&rows), 2).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn circles_reach_further_up_and_left() {
+        let mirrored = ["..#..", ".###.", ".####", ".###.", "....."];
+        assert!(scan_image_for_circles(&image(&mirrored), 2).is_empty());
+    }
+
+    #[test]
+    fn circles_go_around_holes() {
+        let holed = image(&["######", "######", "######", "##.###", "######", "######"]);
+        let circles = scan_image_for_circles(&holed, 1);
+        assert_eq!(circles.len(), 8);
+        assert!(!circles.contains(&IVec2::new(-1, 0)));
+    }
+}
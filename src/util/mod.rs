@@ -1,4 +1,6 @@
 pub mod common_logic;
 pub mod ecs;
+pub mod image_geometry;
 pub mod macros;
 pub mod trait_unions;
+pub mod user_dir;
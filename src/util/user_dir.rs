@@ -0,0 +1,26 @@
+//! Where per-user files like the saved settings live.
+
+use std::path::PathBuf;
+
+const APP_DIR: &str = "chai-reaction";
+
+/// The game's folder in the platform's per-user config directory, `None` where there is
+/// no file system to write to.
+pub fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_arch = "wasm32") {
+        return None;
+    }
+    let env = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let base = if cfg!(windows) {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|home| home.join(".config")))
+    };
+    base.map(|base| base.join(APP_DIR))
+}